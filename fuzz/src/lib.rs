@@ -0,0 +1,17 @@
+//! Fuzz targets feeding arbitrary text to each day's parser and, when it
+//! accepts the text, to both parts of the solver.
+//!
+//! Malformed input has to come back as a `ParseError`, and input a solver
+//! cannot answer as a `SolveError`; any panic is a bug.
+//! Run one day with `cargo +nightly fuzz run day6`.
+
+use adventofcode::solution;
+
+pub fn run(day: u32, input: &str) {
+    let day = solution::find(day).expect("no such day");
+    if let Ok(parsed) = day.parse(input) {
+        for part in [1, 2] {
+            let _ = day.solve(&parsed, part);
+        }
+    }
+}
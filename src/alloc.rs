@@ -0,0 +1,147 @@
+//! Heap usage per solver, from a counting global allocator.
+//!
+//! The allocator is only installed with the `alloc-stats` feature, since the
+//! bookkeeping slows down allocation-heavy solvers:
+//!
+//! ```text
+//! cargo run --release --features alloc-stats -- all
+//! ```
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+/// Wraps the system allocator, counting what each thread allocates.
+pub struct Counting;
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+thread_local! {
+    /// Bytes allocated minus bytes freed by this thread. Memory freed by
+    /// another thread than the one that allocated it makes this drift.
+    static CURRENT: Cell<isize> = const { Cell::new(0) };
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+    static COUNT: Cell<usize> = const { Cell::new(0) };
+}
+
+fn record(grow: isize, allocations: usize) {
+    // Thread locals are gone while a thread shuts down; those allocations go uncounted.
+    let _ = CURRENT.try_with(|current| {
+        let now = current.get() + grow;
+        current.set(now);
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(now)));
+        let _ = COUNT.try_with(|count| count.set(count.get() + allocations));
+    });
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record(layout.size() as isize, 1);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record(layout.size() as isize, 1);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record(-(layout.size() as isize), 0);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            record(new_size as isize - layout.size() as isize, 1);
+        }
+        new
+    }
+}
+
+/// What a piece of code allocated on its thread.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// The most heap it held at once, beyond what was live when it started.
+    pub peak_bytes: usize,
+    /// Allocations and reallocations made.
+    pub allocations: usize,
+}
+
+/// Whether the counting allocator is installed.
+pub const fn enabled() -> bool {
+    cfg!(feature = "alloc-stats")
+}
+
+/// Runs `f`, returning what it allocated if the counting allocator is installed.
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, Option<Usage>) {
+    if !enabled() {
+        return (f(), None);
+    }
+    let start = CURRENT.get();
+    let outer_peak = PEAK.replace(start);
+    let count = COUNT.get();
+    let result = f();
+    let peak = PEAK.get();
+    // An enclosing measurement still sees this one's peak.
+    PEAK.set(peak.max(outer_peak));
+    let usage = Usage {
+        peak_bytes: (peak - start).max(0) as usize,
+        allocations: COUNT.get() - count,
+    };
+    (result, Some(usage))
+}
+
+/// `bytes` in the largest binary unit that keeps it at least 1, e.g. `1.5 MiB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", UNITS[unit]),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 << 20), "3.0 MiB");
+    }
+
+    #[test]
+    fn test_measure() {
+        let ((inner, v), outer) = measure(|| {
+            let (_, inner) = measure(|| vec![0u8; 1000]);
+            (inner, vec![0u8; 4000])
+        });
+        assert_eq!(v.len(), 4000);
+        match (inner, outer) {
+            (Some(inner), Some(outer)) => {
+                assert_eq!(inner.allocations, 1);
+                assert!(inner.peak_bytes >= 1000);
+                assert!(outer.peak_bytes >= 4000);
+                assert!(outer.allocations >= 2);
+            }
+            (inner, outer) => assert!(!enabled() && inner.is_none() && outer.is_none()),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt;
+
+use crate::utils::SolveError;
+
+/// A solver's result, kept typed so that it is rendered and compared by value
+/// rather than by its string form.
+#[derive(Clone, Debug)]
+pub enum Answer {
+    Signed(i64),
+    Unsigned(u64),
+    Big(i128),
+    Text(String),
+    /// A multi-line answer such as letters drawn in ASCII art, one row per line.
+    Grid(Vec<String>),
+}
+
+impl Answer {
+    pub fn grid<S: Into<String>>(rows: impl IntoIterator<Item = S>) -> Self {
+        Answer::Grid(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Parses an optionally negative integer into the narrowest variant that holds it.
+    pub fn parse_int(s: &str) -> Option<Self> {
+        let digits = s.strip_prefix('-').unwrap_or(s);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        if let Ok(n) = s.parse() {
+            Some(Answer::Unsigned(n))
+        } else if let Ok(n) = s.parse() {
+            Some(Answer::Signed(n))
+        } else {
+            s.parse().ok().map(Answer::Big)
+        }
+    }
+
+    /// Text with several lines becomes a grid, so that it matches one.
+    pub fn text(s: &str) -> Self {
+        match s.contains('\n') {
+            true => Answer::grid(s.lines()),
+            false => Answer::Text(s.to_string()),
+        }
+    }
+
+    pub fn is_int(&self) -> bool {
+        self.int().is_some()
+    }
+
+    pub fn is_multiline(&self) -> bool {
+        matches!(self, Answer::Grid(rows) if rows.len() > 1)
+    }
+
+    fn int(&self) -> Option<i128> {
+        match *self {
+            Answer::Signed(n) => Some(n.into()),
+            Answer::Unsigned(n) => Some(n.into()),
+            Answer::Big(n) => Some(n),
+            Answer::Text(_) | Answer::Grid(_) => None,
+        }
+    }
+}
+
+/// Integers are equal by value whatever their width; an integer never equals
+/// text that happens to spell it.
+impl PartialEq for Answer {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Answer::Text(a), Answer::Text(b)) => a == b,
+            (Answer::Grid(a), Answer::Grid(b)) => a == b,
+            _ => matches!((self.int(), other.int()), (Some(a), Some(b)) if a == b),
+        }
+    }
+}
+
+impl Eq for Answer {}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Signed(n) => write!(f, "{n}"),
+            Answer::Unsigned(n) => write!(f, "{n}"),
+            Answer::Big(n) => write!(f, "{n}"),
+            Answer::Text(s) => write!(f, "{s}"),
+            Answer::Grid(rows) => write!(f, "{}", rows.join("\n")),
+        }
+    }
+}
+
+macro_rules! from_int {
+    ($variant:ident: $($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(n: $t) -> Self {
+                Answer::$variant(n as _)
+            }
+        })*
+    };
+}
+
+from_int!(Signed: i8, i16, i32, i64, isize);
+from_int!(Unsigned: u8, u16, u32, u64, usize);
+from_int!(Big: i128);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_string())
+    }
+}
+
+impl From<Vec<String>> for Answer {
+    fn from(rows: Vec<String>) -> Self {
+        Answer::Grid(rows)
+    }
+}
+
+/// What a part of a solution returns: anything that converts into an
+/// `Answer`, or a `Result` of it for parts that can fail on parsed input.
+pub trait IntoAnswer {
+    fn into_answer(self) -> Result<Answer, SolveError>;
+}
+
+macro_rules! into_answer {
+    ($($t:ty),*) => {
+        $(impl IntoAnswer for $t {
+            fn into_answer(self) -> Result<Answer, SolveError> {
+                Ok(self.into())
+            }
+        })*
+    };
+}
+
+into_answer!(i8, i16, i32, i64, isize, i128);
+into_answer!(u8, u16, u32, u64, usize);
+into_answer!(String, &str, Vec<String>, Answer);
+
+impl<T: IntoAnswer> IntoAnswer for Result<T, SolveError> {
+    fn into_answer(self) -> Result<Answer, SolveError> {
+        self?.into_answer()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_eq() {
+        assert_eq!(Answer::from(6u64), Answer::from(6i32));
+        assert_eq!(Answer::from(-6i64), Answer::Big(-6));
+        assert_ne!(Answer::from(6u64), Answer::from("6"));
+        assert_eq!(Answer::text("#.\n.#"), Answer::grid(["#.", ".#"]));
+        assert_ne!(Answer::text("#."), Answer::grid(["#."]));
+    }
+
+    #[test]
+    fn test_parse_int() {
+        assert_eq!(Answer::parse_int("42"), Some(Answer::Unsigned(42)));
+        assert!(matches!(
+            Answer::parse_int("-42"),
+            Some(Answer::Signed(-42))
+        ));
+        assert!(matches!(
+            Answer::parse_int("-99999999999999999999"),
+            Some(Answer::Big(-99999999999999999999))
+        ));
+        assert_eq!(Answer::parse_int("4x"), None);
+        assert_eq!(Answer::parse_int("-"), None);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Answer::from(-3i32).to_string(), "-3");
+        assert_eq!(Answer::grid(["#.", ".#"]).to_string(), "#.\n.#");
+    }
+}
@@ -0,0 +1,192 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use nom::{
+    branch::alt,
+    bytes::complete::{escaped_transform, is_not, tag},
+    character::complete::{char, digit1, space0},
+    combinator::{all_consuming, map, map_opt, map_res, opt, recognize, rest, value},
+    sequence::{delimited, pair, preceded, separated_pair, terminated},
+    IResult,
+};
+
+use crate::answer::Answer;
+
+/// Known-good answers keyed by `(day, part)`, stored as a small TOML file:
+///
+/// ```toml
+/// [day1]
+/// part1 = 1100
+/// part2 = "some text"
+/// ```
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Answers(BTreeMap<(u32, u32), Answer>);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: Answer },
+    Unknown,
+}
+
+#[derive(Clone)]
+enum Line {
+    Blank,
+    Day(u32),
+    Part(u32, Answer),
+}
+
+fn parse_line(s: &str) -> IResult<&str, Line> {
+    let num = || map_res(digit1, str::parse);
+    let comment = opt(preceded(char('#'), rest));
+    let day = delimited(tag("[day"), num(), char(']'));
+    let string = delimited(
+        char('"'),
+        map(
+            opt(escaped_transform(
+                is_not("\"\\"),
+                '\\',
+                alt((
+                    value("\\", char('\\')),
+                    value("\"", char('"')),
+                    value("\n", char('n')),
+                )),
+            )),
+            |s| Answer::text(&s.unwrap_or_default()),
+        ),
+        char('"'),
+    );
+    let int = map_opt(recognize(pair(opt(char('-')), digit1)), Answer::parse_int);
+    let part = separated_pair(
+        preceded(tag("part"), num()),
+        delimited(space0, char('='), space0),
+        alt((string, int)),
+    );
+    all_consuming(delimited(
+        space0,
+        alt((
+            map(day, Line::Day),
+            map(part, |(part, answer)| Line::Part(part, answer)),
+            value(Line::Blank, space0),
+        )),
+        terminated(space0, comment),
+    ))(s)
+}
+
+impl Answers {
+    /// Loads the answers file, treating a missing file as empty.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(io::Error::new(e.kind(), format!("{}: {e}", path.display()))),
+        };
+        Self::parse(&text).map_err(|line| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{line}: malformed answers entry", path.display()),
+            )
+        })
+    }
+
+    /// Parses the format written by `save`, returning the bad line number on error.
+    fn parse(text: &str) -> Result<Self, usize> {
+        let mut answers = Self::default();
+        let mut day = None;
+        for (i, line) in text.lines().enumerate() {
+            match parse_line(line).map_err(|_| i + 1)?.1 {
+                Line::Blank => {}
+                Line::Day(d) => day = Some(d),
+                Line::Part(part, answer) => {
+                    answers.0.insert((day.ok_or(i + 1)?, part), answer);
+                }
+            }
+        }
+        Ok(answers)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_toml())
+    }
+
+    fn to_toml(&self) -> String {
+        let mut text = String::new();
+        let mut last_day = None;
+        for (&(day, part), answer) in &self.0 {
+            if last_day != Some(day) {
+                if last_day.is_some() {
+                    text.push('\n');
+                }
+                text += &format!("[day{day}]\n");
+                last_day = Some(day);
+            }
+            if answer.is_int() {
+                text += &format!("part{part} = {answer}\n");
+            } else {
+                let escaped = answer
+                    .to_string()
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('\n', "\\n");
+                text += &format!("part{part} = \"{escaped}\"\n");
+            }
+        }
+        text
+    }
+
+    pub fn insert(&mut self, day: u32, part: u32, answer: Answer) {
+        self.0.insert((day, part), answer);
+    }
+
+    pub fn check(&self, day: u32, part: u32, answer: &Answer) -> Verdict {
+        match self.0.get(&(day, part)) {
+            None => Verdict::Unknown,
+            Some(expected) if expected == answer => Verdict::Pass,
+            Some(expected) => Verdict::Fail {
+                expected: expected.clone(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SAMPLE: &str = "
+        # Answers for the real inputs
+        [day1]
+        part1 = 3
+        part2 = -6  # negative
+
+        [day10]
+        part1 = \"two\\nlines \\\"quoted\\\"\"";
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(SAMPLE).unwrap();
+        assert_eq!(answers.check(1, 1, &3usize.into()), Verdict::Pass);
+        assert_eq!(
+            answers.check(1, 1, &"3".into()),
+            Verdict::Fail {
+                expected: 3u32.into()
+            }
+        );
+        assert_eq!(
+            answers.check(1, 2, &6.into()),
+            Verdict::Fail {
+                expected: (-6).into()
+            }
+        );
+        let grid = Answer::grid(["two", "lines \"quoted\""]);
+        assert_eq!(answers.check(10, 1, &grid), Verdict::Pass);
+        assert_eq!(answers.check(10, 2, &1.into()), Verdict::Unknown);
+        assert_eq!(Answers::parse("part1 = 3"), Err(1));
+        assert_eq!(Answers::parse("[day1]\npart1 = 3 3"), Err(2));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let answers = Answers::parse(SAMPLE).unwrap();
+        assert_eq!(Answers::parse(&answers.to_toml()), Ok(answers));
+    }
+}
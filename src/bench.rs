@@ -0,0 +1,268 @@
+use std::{collections::BTreeMap, fs, io, path::Path, time::Instant};
+
+use crate::alloc::{self, Usage};
+
+/// Timing statistics over repeated runs, in microseconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+    pub min: f64,
+    pub median: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    /// Heap used by one run, with the `alloc-stats` feature.
+    pub memory: Option<Usage>,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &mut [f64]) -> Self {
+        assert!(!samples.is_empty(), "no samples");
+        samples.sort_by(f64::total_cmp);
+        let n = samples.len();
+        let median = match n % 2 {
+            0 => (samples[n / 2 - 1] + samples[n / 2]) / 2.0,
+            _ => samples[n / 2],
+        };
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n as f64;
+        Self {
+            min: samples[0],
+            median,
+            mean,
+            stddev: variance.sqrt(),
+            memory: None,
+        }
+    }
+}
+
+/// Runs `f` a few times to warm up, then `runs` times while timing each call.
+///
+/// Memory is taken from the first warm-up run, as every run allocates alike.
+pub fn measure(runs: usize, mut f: impl FnMut()) -> Stats {
+    let ((), memory) = alloc::measure(&mut f);
+    for _ in 1..usize::max(1, runs / 10) {
+        f();
+    }
+    let mut samples: Vec<f64> = (0..runs.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed().as_secs_f64() * 1e6
+        })
+        .collect();
+    Stats {
+        memory,
+        ..Stats::from_samples(&mut samples)
+    }
+}
+
+/// Saved statistics keyed by `(day, part)`.
+#[derive(Debug, Default, PartialEq)]
+pub struct Baseline(pub BTreeMap<(u32, u32), Stats>);
+
+impl Baseline {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+        Self::parse(&text).map_err(|line| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{line}: malformed baseline entry", path.display()),
+            )
+        })
+    }
+
+    /// Parses the format written by `save`, returning the bad line number on error.
+    fn parse(text: &str) -> Result<Self, usize> {
+        let mut baseline = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<_> = line.split_whitespace().collect();
+            // Memory is only there for runs with the `alloc-stats` feature.
+            let (fields, memory) = match &fields[..] {
+                [timing @ .., peak_bytes, allocations] if timing.len() == 6 => {
+                    match (peak_bytes.parse(), allocations.parse()) {
+                        (Ok(peak_bytes), Ok(allocations)) => (
+                            timing,
+                            Some(Usage {
+                                peak_bytes,
+                                allocations,
+                            }),
+                        ),
+                        _ => return Err(i + 1),
+                    }
+                }
+                fields => (fields, None),
+            };
+            let [day, part, min, median, mean, stddev] = fields[..] else {
+                return Err(i + 1);
+            };
+            let key = (day.parse(), part.parse());
+            let stats = [min, median, mean, stddev].map(str::parse::<f64>);
+            match (key, stats) {
+                ((Ok(day), Ok(part)), [Ok(min), Ok(median), Ok(mean), Ok(stddev)]) => {
+                    let stats = Stats {
+                        min,
+                        median,
+                        mean,
+                        stddev,
+                        memory,
+                    };
+                    baseline.0.insert((day, part), stats);
+                }
+                _ => return Err(i + 1),
+            }
+        }
+        Ok(baseline)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut text = String::from(
+            "# day part min_us median_us mean_us stddev_us [peak_bytes allocations]\n",
+        );
+        for ((day, part), s) in &self.0 {
+            text += &format!(
+                "{day} {part} {:.3} {:.3} {:.3} {:.3}",
+                s.min, s.median, s.mean, s.stddev
+            );
+            if let Some(m) = s.memory {
+                text += &format!(" {} {}", m.peak_bytes, m.allocations);
+            }
+            text.push('\n');
+        }
+        fs::write(path, text)
+    }
+}
+
+/// Relative change of the median against the baseline, in percent.
+fn change(stats: &Stats, baseline: &Stats) -> f64 {
+    (stats.median - baseline.median) / baseline.median * 100.0
+}
+
+/// Relative change of the peak heap against the baseline, in percent, if
+/// both runs measured it.
+fn memory_change(stats: &Stats, baseline: &Stats) -> Option<f64> {
+    let (new, old) = (stats.memory?.peak_bytes, baseline.memory?.peak_bytes);
+    Some((new as f64 - old as f64) / old.max(1) as f64 * 100.0)
+}
+
+/// Prints a table of `results`, comparing medians and peak heap usage with
+/// `baseline` if given.
+///
+/// Returns the number of solvers whose median or peak heap usage regressed
+/// by more than `threshold` percent.
+pub fn report(results: &Baseline, baseline: Option<&Baseline>, threshold: f64) -> usize {
+    let mut regressions = 0;
+    let memory = results.0.values().any(|s| s.memory.is_some());
+    print!(
+        "Day  Part  {:>12}  {:>12}  {:>12}  {:>12}",
+        "min (µs)", "median (µs)", "mean (µs)", "stddev (µs)"
+    );
+    if memory {
+        print!("  {:>10}  {:>8}", "peak", "allocs");
+    }
+    match (baseline.is_some(), memory) {
+        (false, _) => println!(),
+        (true, false) => println!("  change"),
+        (true, true) => println!("  change   peak change"),
+    }
+    for (&(day, part), stats) in &results.0 {
+        print!(
+            "{day:>3}  {part:>4}  {:>12.1}  {:>12.1}  {:>12.1}  {:>12.1}",
+            stats.min, stats.median, stats.mean, stats.stddev
+        );
+        match stats.memory {
+            Some(m) => print!(
+                "  {:>10}  {:>8}",
+                alloc::format_bytes(m.peak_bytes),
+                m.allocations
+            ),
+            None if memory => print!("  {:>10}  {:>8}", "-", "-"),
+            None => {}
+        }
+        match baseline.map(|b| b.0.get(&(day, part))) {
+            None => println!(),
+            Some(None) => println!("  (new)"),
+            Some(Some(old)) => {
+                let change = change(stats, old);
+                let memory_change = memory_change(stats, old);
+                print!("  {change:>+6.1}%");
+                if let Some(memory_change) = memory_change {
+                    print!("  {memory_change:>+10.1}%");
+                }
+                if change > threshold || memory_change.is_some_and(|c| c > threshold) {
+                    regressions += 1;
+                    println!("  REGRESSION");
+                } else {
+                    println!();
+                }
+            }
+        }
+    }
+    regressions
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::from_samples(&mut [4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.median, 2.5);
+        assert_eq!(stats.mean, 2.5);
+        assert_eq!(stats.stddev, 1.25f64.sqrt());
+    }
+
+    #[test]
+    fn test_baseline_parse() {
+        let baseline = Baseline::parse(
+            "
+            # day part min_us median_us mean_us stddev_us
+            3 2 1.000 2.000 3.000 0.500",
+        )
+        .unwrap();
+        let stats = Stats {
+            min: 1.0,
+            median: 2.0,
+            mean: 3.0,
+            stddev: 0.5,
+            memory: None,
+        };
+        assert_eq!(baseline.0.get(&(3, 2)), Some(&stats));
+        assert_eq!(Baseline::parse("1 1 1.0 2.0\n"), Err(1));
+        let baseline = Baseline::parse("3 2 1.0 2.0 3.0 0.5 4096 12").unwrap();
+        let memory = Usage {
+            peak_bytes: 4096,
+            allocations: 12,
+        };
+        assert_eq!(baseline.0[&(3, 2)].memory, Some(memory));
+        assert_eq!(Baseline::parse("3 2 1.0 2.0 3.0 0.5 4096 x"), Err(1));
+    }
+
+    #[test]
+    fn test_change() {
+        let old = Stats::from_samples(&mut [100.0]);
+        let new = Stats::from_samples(&mut [125.0]);
+        assert_eq!(change(&new, &old), 25.0);
+        assert_eq!(memory_change(&new, &old), None);
+        let usage = |peak_bytes| {
+            Some(Usage {
+                peak_bytes,
+                allocations: 1,
+            })
+        };
+        let old = Stats {
+            memory: usage(1000),
+            ..old
+        };
+        let new = Stats {
+            memory: usage(1500),
+            ..new
+        };
+        assert_eq!(memory_change(&new, &old), Some(50.0));
+    }
+}
@@ -1,15 +1,15 @@
-use nom::{
-    character::complete::{digit1, multispace0},
-    combinator::{fail, map, map_res, recognize},
-    multi::many0,
-    sequence::{pair, preceded},
-    IResult,
+use nom::{combinator::fail, sequence::pair, IResult};
+
+use crate::{
+    generate::{self, Rng, Size},
+    solution::{Sample, Solution},
+    utils::{
+        parse::{lines_of, unsigned},
+        parse_all, ParseError,
+    },
 };
 
 fn parse(s: &str) -> IResult<&str, Vec<i32>> {
-    fn int(s: &str) -> IResult<&str, i32> {
-        map_res(recognize(digit1), str::parse)(s)
-    }
     fn dir(s: &str) -> IResult<&str, i32> {
         match s.split_at_checked(1) {
             Some(("L", rest)) => Ok((rest, -1)),
@@ -17,62 +17,123 @@ fn parse(s: &str) -> IResult<&str, Vec<i32>> {
             _ => fail(s),
         }
     }
-    many0(preceded(multispace0, map(pair(dir, int), |(a, b)| a * b)))(s)
+    let turn = pair(dir, unsigned::<i32>);
+    let (rest, turns) = lines_of(turn)(s)?;
+    Ok((rest, turns.into_iter().map(|(a, b)| a * b).collect()))
 }
 
-pub fn solve(s: &str) -> u32 {
-    let mut dial = 50;
-    let mut zeros = 0;
-    for turn in parse(s).unwrap().1 {
-        dial += turn;
-        dial = dial.rem_euclid(100);
-        if dial == 0 {
-            zeros += 1;
+const SAMPLE: &str = "
+        L68
+        L30
+        R48
+        L5
+        R60
+        L55
+        L1
+        L99
+        R14
+        L82";
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u32 = 1;
+    const SAMPLE: Sample = Sample {
+        input: SAMPLE,
+        part1: Some("3"),
+        part2: Some("6"),
+    };
+
+    type Input = Vec<i32>;
+    type Answer1 = u32;
+    type Answer2 = i64;
+
+    fn parse(s: &str) -> Result<Self::Input, ParseError> {
+        parse_all(s, parse)
+    }
+
+    fn part1(turns: &Self::Input) -> u32 {
+        let mut dial = 50;
+        let mut zeros = 0;
+        for turn in turns {
+            dial = (dial + turn % 100).rem_euclid(100);
+            if dial == 0 {
+                zeros += 1;
+            }
         }
+        zeros
     }
-    zeros
-}
 
-pub fn solve_2(s: &str) -> i32 {
-    let mut dial = 50;
-    let mut zeros = 0;
-    for turn in parse(s).unwrap().1 {
-        if turn.is_negative() && dial == 0 {
-            zeros -= 1;
+    fn part2(turns: &Self::Input) -> Option<i64> {
+        let mut dial = 50;
+        let mut zeros = 0;
+        // Widened, as a turn of `i32::MAX` clicks passes 0 millions of times.
+        for turn in turns.iter().map(|&turn| i64::from(turn)) {
+            if turn.is_negative() && dial == 0 {
+                zeros -= 1;
+            }
+            dial += turn;
+            zeros += match turn.is_negative() {
+                true => (dial - 1).div_euclid(100).abs(),
+                false => dial.div_euclid(100).abs(),
+            };
+            dial = dial.rem_euclid(100);
         }
-        dial += turn;
-        zeros += match turn.is_negative() {
-            true => (dial - 1).div_euclid(100).abs(),
-            false => dial.div_euclid(100).abs(),
-        };
-        dial = dial.rem_euclid(100);
+        Some(zeros)
+    }
+
+    /// `count` rotations of up to `width` digits.
+    fn generate(rng: &mut Rng, size: Size) -> Option<String> {
+        // Turns fit in an `i32`.
+        let digits = size.width_or(3).clamp(1, 9) as u32;
+        let turns = (0..size.count_or(4500)).map(|_| {
+            let dir = if rng.chance(0.5) { 'L' } else { 'R' };
+            format!("{dir}{}", rng.number(digits))
+        });
+        Some(generate::lines(turns))
     }
-    zeros
 }
 
 #[cfg(test)]
 mod test {
+    use proptest::prelude::*;
+
     use super::*;
 
-    const SAMPLE: &'static str = "
-        L68
-        L30
-        R48
-        L5
-        R60
-        L55
-        L1
-        L99
-        R14
-        L82";
+    /// Turns the dial one click at a time, counting every time it shows 0.
+    fn naive(turns: &[i32]) -> (u32, i64) {
+        let mut dial = 50;
+        let (mut stops, mut passes) = (0, 0);
+        for &turn in turns {
+            for _ in 0..turn.abs() {
+                dial = (dial + turn.signum()).rem_euclid(100);
+                if dial == 0 {
+                    passes += 1;
+                }
+            }
+            if dial == 0 {
+                stops += 1;
+            }
+        }
+        (stops, passes)
+    }
 
     #[test]
     fn test_sample() {
-        assert_eq!(solve(SAMPLE), 3);
+        assert_eq!(Day1::part1(&Day1::parse(SAMPLE).unwrap()), 3);
     }
 
     #[test]
     fn test_sample_2() {
-        assert_eq!(solve_2(SAMPLE), 6);
+        assert_eq!(Day1::part2(&Day1::parse(SAMPLE).unwrap()), Some(6));
+    }
+
+    proptest! {
+        #[test]
+        fn test_naive(turns in prop::collection::vec(-350..350i32, 0..50)) {
+            let (stops, passes) = naive(&turns);
+            prop_assert_eq!(Day1::part1(&turns), stops);
+            prop_assert_eq!(Day1::part2(&turns), Some(passes));
+        }
     }
 }
@@ -1,24 +1,43 @@
 use std::{collections::HashMap, iter::zip};
 
+use itertools::Itertools;
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    character::complete::{char, digit1, multispace0, space0},
-    combinator::{map_res, value},
-    multi::{many0, many1, separated_list1},
+    character::complete::{char, space0},
+    combinator::{consumed, map, value},
+    multi::many1,
     sequence::{delimited, preceded, tuple},
     IResult,
 };
 
-fn parse(s: &str) -> IResult<&str, Vec<(Vec<usize>, Vec<Vec<usize>>, Vec<usize>)>> {
-    let nums = || separated_list1(tag(","), map_res(digit1, str::parse));
+use crate::{
+    generate::{self, Rng, Size},
+    solution::{Sample, Solution},
+    utils::{
+        log,
+        parse::{comma_list, lines_of, spaced, unsigned},
+        parse_all, ParseError, SolveError, Spanned,
+    },
+};
+
+type Machine = (Vec<usize>, Vec<Vec<usize>>, Vec<usize>);
+
+/// Button combinations are enumerated as `u16` masks.
+const MAX_BUTTONS: usize = 15;
+
+fn parse(s: &str) -> IResult<&str, Vec<Spanned<'_, Machine>>> {
+    // Requirements that fit in a `u32` keep press counts within a `usize`.
+    let requirement = map(unsigned::<u32>, |n| n as usize);
     let diagram = many1(alt((value(0, char('.')), value(1, char('#')))));
     let line = tuple((
-        preceded(multispace0, delimited(tag("["), diagram, tag("]"))),
-        many1(preceded(space0, delimited(tag("("), nums(), tag(")")))),
-        preceded(space0, delimited(tag("{"), nums(), tag("}"))),
+        delimited(char('['), diagram, char(']')),
+        spaced(delimited(char('('), comma_list(unsigned), char(')'))),
+        preceded(
+            space0,
+            delimited(char('{'), comma_list(requirement), char('}')),
+        ),
     ));
-    many0(line)(s)
+    lines_of(consumed(line))(s)
 }
 
 fn parity_presses(
@@ -43,8 +62,8 @@ fn parity_presses(
     result
 }
 
-fn bifurcate(target: Vec<usize>, buttons: Vec<Vec<usize>>) -> usize {
-    enum DFS {
+fn bifurcate(target: Vec<usize>, buttons: Vec<Vec<usize>>) -> Option<usize> {
+    enum Dfs {
         Recurse { state: Vec<usize> },
         DoubleOffset { offset: usize },
         Min { state: Vec<usize>, n_nodes: usize },
@@ -52,12 +71,12 @@ fn bifurcate(target: Vec<usize>, buttons: Vec<Vec<usize>>) -> usize {
     let parity_cache = parity_presses(target.len(), &buttons);
     let mut memo: HashMap<Vec<usize>, Option<usize>> = HashMap::new();
     let mut retval: Vec<Option<usize>> = Vec::new();
-    let mut stack: Vec<_> = vec![DFS::Recurse {
+    let mut stack: Vec<_> = vec![Dfs::Recurse {
         state: target.clone(),
     }];
     while let Some(dfs) = stack.pop() {
         match dfs {
-            DFS::Recurse { state } => {
+            Dfs::Recurse { state } => {
                 if let Some(&ret) = memo.get(&state) {
                     retval.push(ret);
                     continue;
@@ -75,19 +94,19 @@ fn bifurcate(target: Vec<usize>, buttons: Vec<Vec<usize>>) -> usize {
                         }
                         let mut state = state.clone();
                         zip(adjust, &mut state).for_each(|(a, b)| *b = (*b - *a) / 2);
-                        stack.push(DFS::DoubleOffset { offset });
-                        stack.push(DFS::Recurse { state });
+                        stack.push(Dfs::DoubleOffset { offset });
+                        stack.push(Dfs::Recurse { state });
                     }
                 }
                 let n_nodes = (stack.len() - top) / 2;
-                stack.insert(top, DFS::Min { state, n_nodes });
+                stack.insert(top, Dfs::Min { state, n_nodes });
             }
-            DFS::DoubleOffset { offset } => {
+            Dfs::DoubleOffset { offset } => {
                 if let Some(ret) = retval.last_mut().unwrap() {
                     *ret = *ret * 2 + offset;
                 }
             }
-            DFS::Min { n_nodes, state } => {
+            Dfs::Min { n_nodes, state } => {
                 let nodes = retval.split_off(retval.len() - n_nodes);
                 let min = nodes.into_iter().flatten().min();
                 retval.push(min);
@@ -95,56 +114,213 @@ fn bifurcate(target: Vec<usize>, buttons: Vec<Vec<usize>>) -> usize {
             }
         }
     }
-    retval[0].unwrap()
+    retval[0]
 }
 
-pub fn solve(s: &str) -> usize {
-    let machines = parse(s).unwrap().1;
-    machines
-        .into_iter()
-        .map(|(target, buttons, _)| {
-            let combos = parity_presses(target.len(), &buttons);
-            combos
-                .get(&target)
-                .unwrap()
-                .iter()
-                .map(|&(n, _)| n)
-                .min()
-                .unwrap()
-        })
-        .sum()
-}
+const SAMPLE: &str = "
+    [.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}
+    [...#.] (0,2,3,4) (2,3) (0,4) (0,1,2) (1,2,3,4) {7,5,12,7,2}
+    [.###.#] (0,1,2,3,4) (0,3,4) (0,1,2,4,5) (1,2) {10,11,11,5,10,5}
+        ";
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u32 = 10;
+    const SAMPLE: Sample = Sample {
+        input: SAMPLE,
+        part1: Some("7"),
+        part2: Some("33"),
+    };
 
-pub fn solve_2(s: &str) -> usize {
-    let machines = parse(s).unwrap().1;
-    machines
-        .into_iter()
-        .enumerate()
-        .map(|(i, (_, buttons, target))| {
-            let result = bifurcate(target, buttons);
-            println!("{i}: {result}");
-            result
-        })
-        .sum()
+    type Input = Vec<Machine>;
+    type Answer1 = Result<usize, SolveError>;
+    type Answer2 = Result<usize, SolveError>;
+
+    fn parse(s: &str) -> Result<Self::Input, ParseError> {
+        parse_all(s, parse)?
+            .into_iter()
+            .map(|(text, (diagram, buttons, joltage))| {
+                let lights = diagram.len();
+                let message = if buttons.len() > MAX_BUTTONS {
+                    format!("expected at most {MAX_BUTTONS} buttons")
+                } else if buttons.iter().flatten().any(|&i| i >= lights) {
+                    format!("expected buttons wired to the {lights} lights")
+                } else if joltage.len() != lights {
+                    format!("expected {lights} joltage requirements")
+                } else {
+                    return Ok((diagram, buttons, joltage));
+                };
+                Err(ParseError::at(s, text, message))
+            })
+            .collect()
+    }
+
+    fn part1(machines: &Self::Input) -> Result<usize, SolveError> {
+        machines
+            .iter()
+            .enumerate()
+            .map(|(i, (target, buttons, _))| {
+                let combos = parity_presses(target.len(), buttons);
+                combos
+                    .get(target)
+                    .and_then(|presses| presses.iter().map(|&(n, _)| n).min())
+                    .ok_or_else(|| SolveError::new(format!("machine {i}: lights unreachable")))
+            })
+            .sum()
+    }
+
+    fn part2(machines: &Self::Input) -> Option<Result<usize, SolveError>> {
+        let total = machines
+            .iter()
+            .enumerate()
+            .map(|(i, (_, buttons, target))| {
+                let result = bifurcate(target.clone(), buttons.clone())
+                    .ok_or_else(|| SolveError::new(format!("machine {i}: joltage unreachable")))?;
+                log::debug(format_args!("machine {i}: {result}"));
+                log::progress(i + 1, machines.len());
+                Ok(result)
+            })
+            .sum();
+        Some(total)
+    }
+
+    /// `count` machines of up to `width` lights. The diagram and joltage of
+    /// each come from pressing its buttons, so that both can be reached.
+    fn generate(rng: &mut Rng, size: Size) -> Option<String> {
+        let width = size.width_or(10).max(1) as u64;
+        let machines = (0..size.count_or(160)).map(|_| {
+            let lights = rng.range(1..=width) as usize;
+            let n_buttons = rng.range(1..=(lights as u64 + 3).min(MAX_BUTTONS as u64));
+            let (mut diagram, mut joltage) = (vec![false; lights], vec![0; lights]);
+            let mut buttons = Vec::new();
+            for _ in 0..n_buttons {
+                let mut wiring: Vec<_> = (0..lights).filter(|_| rng.chance(0.5)).collect();
+                if wiring.is_empty() {
+                    wiring.push(rng.index(lights));
+                }
+                let toggle = rng.chance(0.5);
+                let presses = rng.range(0..=30);
+                for &i in &wiring {
+                    diagram[i] ^= toggle;
+                    joltage[i] += presses;
+                }
+                buttons.push(format!("({})", wiring.iter().join(",")));
+            }
+            let diagram: String = diagram
+                .iter()
+                .map(|&on| if on { '#' } else { '.' })
+                .collect();
+            format!(
+                "[{diagram}] {} {{{}}}",
+                buttons.join(" "),
+                joltage.iter().join(",")
+            )
+        });
+        Some(generate::lines(machines))
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::{HashSet, VecDeque};
+
+    use proptest::prelude::*;
+
     use super::*;
 
-    const SAMPLE: &'static str = "
-    [.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}
-    [...#.] (0,2,3,4) (2,3) (0,4) (0,1,2) (1,2,3,4) {7,5,12,7,2}
-    [.###.#] (0,1,2,3,4) (0,3,4) (0,1,2,4,5) (1,2) {10,11,11,5,10,5}
-        ";
+    /// The fewest presses from all zeroes to `target`, by breadth-first search
+    /// over states, where `press` applies a button or gives up on overshooting.
+    fn fewest(
+        target: &[usize],
+        buttons: &[Vec<usize>],
+        press: impl Fn(&mut [usize], &[usize]) -> bool,
+    ) -> usize {
+        let start = vec![0; target.len()];
+        let mut seen = HashSet::from([start.clone()]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some((state, presses)) = queue.pop_front() {
+            if state == target {
+                return presses;
+            }
+            for button in buttons {
+                let mut next = state.clone();
+                if press(&mut next, button) && seen.insert(next.clone()) {
+                    queue.push_back((next, presses + 1));
+                }
+            }
+        }
+        panic!("{target:?} is unreachable");
+    }
+
+    fn naive((diagram, buttons, joltage): &Machine) -> (usize, usize) {
+        let toggle = |state: &mut [usize], button: &[usize]| {
+            button.iter().for_each(|&i| state[i] ^= 1);
+            true
+        };
+        let increase = |state: &mut [usize], button: &[usize]| {
+            button.iter().for_each(|&i| state[i] += 1);
+            zip(state, joltage).all(|(s, j)| *s <= *j)
+        };
+        (
+            fewest(diagram, buttons, toggle),
+            fewest(joltage, buttons, increase),
+        )
+    }
+
+    /// A machine whose diagram and joltage come from pressing its buttons, so
+    /// that both can be reached.
+    fn machine() -> impl Strategy<Value = Machine> {
+        (1..=4usize)
+            .prop_flat_map(|n| {
+                let button = prop::sample::subsequence((0..n).collect::<Vec<_>>(), 1..=n);
+                (Just(n), prop::collection::vec(button, 1..=5))
+            })
+            .prop_flat_map(|(n, buttons)| {
+                let presses = prop::collection::vec((any::<bool>(), 0..4usize), buttons.len());
+                (Just(n), Just(buttons), presses)
+            })
+            .prop_map(|(n, buttons, presses)| {
+                let (mut diagram, mut joltage) = (vec![0; n], vec![0; n]);
+                for (button, (toggle, times)) in zip(&buttons, presses) {
+                    for &i in button {
+                        diagram[i] ^= toggle as usize;
+                        joltage[i] += times;
+                    }
+                }
+                (diagram, buttons, joltage)
+            })
+    }
 
     #[test]
     fn test_sample() {
-        assert_eq!(solve(SAMPLE), 7);
+        assert_eq!(Day10::part1(&Day10::parse(SAMPLE).unwrap()), Ok(7));
     }
 
     #[test]
     fn test_sample_2() {
-        assert_eq!(solve_2(SAMPLE), 33);
+        assert_eq!(Day10::part2(&Day10::parse(SAMPLE).unwrap()), Some(Ok(33)));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = Day10::parse("[.#] (0) {1,2}\n[.#] (0,2) {1,2}")
+            .err()
+            .unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert!(Day10::parse("[.#] (0) {1}").is_err());
+        let machines = Day10::parse("[.#] (0) {1,2}").unwrap();
+        assert!(Day10::part1(&machines).is_err());
+        assert!(matches!(Day10::part2(&machines), Some(Err(_))));
+    }
+
+    proptest! {
+        #[test]
+        fn test_naive(machine in machine()) {
+            let (part1, part2) = naive(&machine);
+            let machines = vec![machine];
+            prop_assert_eq!(Day10::part1(&machines), Ok(part1));
+            prop_assert_eq!(Day10::part2(&machines), Some(Ok(part2)));
+        }
     }
 }
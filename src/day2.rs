@@ -1,90 +1,216 @@
 use nom::{
-    character::complete::{char, digit1, multispace0},
-    combinator::{map_res, recognize},
-    multi::separated_list0,
-    sequence::{pair, preceded},
+    character::complete::{char, multispace0},
+    combinator::map,
+    sequence::{preceded, separated_pair},
     IResult,
 };
 
-fn parse(s: &str) -> IResult<&str, Vec<(u64, u64)>> {
-    fn int(s: &str) -> IResult<&str, u64> {
-        map_res(recognize(digit1), str::parse)(s)
-    }
-    let range = pair(preceded(multispace0, int), preceded(char('-'), int));
-    separated_list0(char(','), range)(s)
+use crate::{
+    generate::{Rng, Size},
+    solution::{Sample, Solution},
+    utils::{
+        parse::{comma_list, unsigned},
+        parse_all, IntervalSet, ParseError, SolveError,
+    },
+};
+
+/// Ranges may be wrapped onto several lines after a comma.
+fn parse(s: &str) -> IResult<&str, IntervalSet<u64>> {
+    let range = map(separated_pair(unsigned, char('-'), unsigned), |(l, h)| {
+        l..=h
+    });
+    let (rest, ranges) = comma_list(preceded(multispace0, range))(s)?;
+    Ok((rest, ranges.into_iter().collect()))
 }
 
-fn repeat(n: u64, count: u32) -> u64 {
-    let scale = 10u64.pow(n.ilog10() + 1);
-    n * (0..count).map(|i| scale.pow(i)).sum::<u64>()
+/// The digits of `n` written `count` times, or `None` past `u64::MAX`.
+fn repeat(n: u64, count: u32) -> Option<u64> {
+    let scale = 10u64.checked_pow(n.ilog10() + 1)?;
+    (0..count).try_fold(0u64, |acc, _| acc.checked_mul(scale)?.checked_add(n))
 }
 
-fn next_invalid_id(id: u64, groups: u32) -> u64 {
+/// The smallest ID above `id` made of `groups` repeats, if it fits in a `u64`.
+fn next_invalid_id(id: u64, groups: u32) -> Option<u64> {
     let length = id.checked_ilog10().unwrap_or(0) + 1;
-    let part = if length % groups == 0 {
+    let part = if length.is_multiple_of(groups) {
         id / 10u64.pow(length - length / groups)
     } else {
-        1 * 10u64.pow(length / groups)
+        10u64.pow(length / groups)
     };
-    match repeat(part, groups) {
-        next_id if next_id > id => next_id,
+    match repeat(part, groups)? {
+        next_id if next_id > id => Some(next_id),
         _ => repeat(part + 1, groups),
     }
 }
 
-fn next_invalid_id_2(id: u64) -> u64 {
+fn next_invalid_id_2(id: u64) -> Option<u64> {
     let length = id.checked_ilog10().unwrap_or(0) + 1;
     (2..=length + 1)
-        .map(|i| next_invalid_id(id, i))
+        .filter_map(|i| next_invalid_id(id, i))
         .min()
-        .unwrap()
 }
 
-pub fn solve(s: &str) -> u64 {
-    let mut sum = 0;
-    for (l, h) in parse(s).unwrap().1 {
-        let mut id = next_invalid_id(l - 1, 2);
-        while id <= h {
-            sum += id;
-            id = next_invalid_id(id, 2);
+/// Sums the IDs in `ranges` that `next` steps through. The ranges of the
+/// real input never overlap; where given ones do, the set has merged them,
+/// so an ID in several of them is counted once.
+fn sum_invalid(
+    ranges: &IntervalSet<u64>,
+    next: impl Fn(u64) -> Option<u64>,
+) -> Result<u64, SolveError> {
+    let mut sum = 0u64;
+    for range in ranges.iter() {
+        let mut id = next(range.start().saturating_sub(1));
+        while let Some(invalid) = id.filter(|id| range.contains(id)) {
+            sum = sum
+                .checked_add(invalid)
+                .ok_or_else(|| SolveError::new("sum of invalid IDs overflows"))?;
+            id = next(invalid);
         }
     }
-    sum
+    Ok(sum)
 }
 
-pub fn solve_2(s: &str) -> u64 {
-    let mut sum = 0;
-    for (l, h) in parse(s).unwrap().1 {
-        let mut id = next_invalid_id_2(l - 1);
-        while id <= h {
-            sum += id;
-            id = next_invalid_id_2(id);
-        }
+const SAMPLE: &str = "
+        11-22,95-115,998-1012,1188511880-1188511890,222220-222224,
+        1698522-1698528,446443-446449,38593856-38593862,565653-565659,
+        824824821-824824827,2121212118-2121212124";
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u32 = 2;
+    const SAMPLE: Sample = Sample {
+        input: SAMPLE,
+        part1: Some("1227775554"),
+        part2: Some("4174379265"),
+    };
+
+    type Input = IntervalSet<u64>;
+    type Answer1 = Result<u64, SolveError>;
+    type Answer2 = Result<u64, SolveError>;
+
+    fn parse(s: &str) -> Result<Self::Input, ParseError> {
+        parse_all(s, parse)
+    }
+
+    fn part1(ranges: &Self::Input) -> Result<u64, SolveError> {
+        sum_invalid(ranges, |id| next_invalid_id(id, 2))
+    }
+
+    fn part2(ranges: &Self::Input) -> Option<Result<u64, SolveError>> {
+        Some(sum_invalid(ranges, next_invalid_id_2))
+    }
+
+    /// Up to `count` ranges of IDs with up to `width` digits.
+    fn generate(rng: &mut Rng, size: Size) -> Option<String> {
+        let digits = size.width_or(10).clamp(1, 19) as u32;
+        let set: IntervalSet<u64> = (0..size.count_or(36))
+            .map(|_| {
+                let (a, b) = (rng.number(digits), rng.number(digits));
+                a.min(b)..=a.max(b)
+            })
+            .collect();
+        // Going through a set keeps the ranges apart, as in the real input,
+        // so that no ID is counted twice.
+        let mut ranges: Vec<_> = set
+            .iter()
+            .map(|r| format!("{}-{}", r.start(), r.end()))
+            .collect();
+        rng.shuffle(&mut ranges);
+        Some(ranges.join(",") + "\n")
     }
-    sum
 }
 
 #[cfg(test)]
 mod test {
+    use std::ops::RangeInclusive;
+
+    use proptest::prelude::*;
+
     use super::*;
 
-    const SAMPLE: &'static str = "
-        11-22,95-115,998-1012,1188511880-1188511890,222220-222224,
-        1698522-1698528,446443-446449,38593856-38593862,565653-565659,
-        824824821-824824827,2121212118-2121212124";
+    /// Whether the digits of `id` are some sequence repeated `groups` times,
+    /// or at least twice if `groups` is `None`.
+    fn is_invalid(id: u64, groups: Option<usize>) -> bool {
+        let digits = id.to_string();
+        (1..digits.len()).any(|len| {
+            digits.len().is_multiple_of(len)
+                && groups.is_none_or(|g| digits.len() / len == g)
+                && digits == digits[..len].repeat(digits.len() / len)
+        })
+    }
+
+    /// Counts each ID once for every range it is in.
+    fn naive(ranges: &[RangeInclusive<u64>], groups: Option<usize>) -> u64 {
+        ranges
+            .iter()
+            .flat_map(|r| r.clone())
+            .filter(|&id| is_invalid(id, groups))
+            .sum()
+    }
+
+    /// Ranges that are apart, as in the real input, so that counting per
+    /// range and per ID agree.
+    fn ranges() -> impl Strategy<Value = Vec<RangeInclusive<u64>>> {
+        let gaps = prop::collection::vec((1..200_000u64, 0..2_000u64), 0..5);
+        gaps.prop_map(|gaps| {
+            let mut start = 0;
+            gaps.into_iter()
+                .map(|(gap, len)| {
+                    start += gap;
+                    let range = start..=start + len;
+                    start += len + 1;
+                    range
+                })
+                .collect()
+        })
+    }
 
     #[test]
     fn test_sample() {
-        assert_eq!(solve(SAMPLE), 1227775554);
+        assert_eq!(Day2::part1(&Day2::parse(SAMPLE).unwrap()), Ok(1227775554));
     }
 
     #[test]
     fn test_sample_2() {
-        assert_eq!(solve_2(SAMPLE), 4174379265);
+        assert_eq!(
+            Day2::part2(&Day2::parse(SAMPLE).unwrap()),
+            Some(Ok(4174379265))
+        );
     }
 
     #[test]
     fn test_next_invalid_id_2() {
-        assert_eq!(next_invalid_id_2(99), 111);
+        assert_eq!(next_invalid_id_2(99), Some(111));
+        assert_eq!(next_invalid_id_2(9_999_999_999), Some(11_111_111_111));
+        assert_eq!(next_invalid_id_2(u64::MAX - 1), None);
+    }
+
+    #[test]
+    fn test_overlapping() {
+        // 22 is in both ranges but only counted once.
+        let ranges = Day2::parse("11-22,15-33").unwrap();
+        assert_eq!(Day2::part1(&ranges), Ok(11 + 22 + 33));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let ranges = Day2::parse("10000000000000000000-18446744073709551615").unwrap();
+        assert!(Day2::part1(&ranges).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn test_next_invalid_id_2_naive(id in 0..1_000_000u64) {
+            let next = (id + 1..).find(|&id| is_invalid(id, None)).unwrap();
+            prop_assert_eq!(next_invalid_id_2(id), Some(next));
+        }
+
+        #[test]
+        fn test_naive(ranges in ranges()) {
+            let set = ranges.iter().cloned().collect();
+            prop_assert_eq!(Day2::part1(&set), Ok(naive(&ranges, Some(2))));
+            prop_assert_eq!(Day2::part2(&set), Some(Ok(naive(&ranges, None))));
+        }
     }
 }
@@ -1,17 +1,22 @@
 use nom::{
-    character::complete::{anychar, multispace0},
-    combinator::map_opt,
-    multi::{many0, many1},
-    sequence::preceded,
+    character::complete::anychar,
+    combinator::{consumed, map_opt},
+    multi::many1,
     IResult,
 };
 
-fn parse(s: &str) -> IResult<&str, Vec<Vec<u32>>> {
+use crate::{
+    generate::{self, Rng, Size},
+    solution::{Sample, Solution},
+    utils::{line_of, parse::lines_of, parse_all, ParseError, SolveError, Spanned},
+};
+
+fn parse(s: &str) -> IResult<&str, Vec<Spanned<'_, Vec<u32>>>> {
     let digit = map_opt(anychar, |d| d.to_digit(10));
-    many0(preceded(multispace0, many1(digit)))(s)
+    lines_of(consumed(many1(digit)))(s)
 }
 
-fn joltage<const N: usize>(bank: Vec<u32>) -> u64 {
+fn joltage<const N: usize>(bank: &[u32]) -> u64 {
     let mut sum = 0;
     let mut i = 0;
     for j in (0..N).rev() {
@@ -22,31 +27,113 @@ fn joltage<const N: usize>(bank: Vec<u32>) -> u64 {
     sum
 }
 
-pub fn solve(s: &str) -> u64 {
-    parse(s).unwrap().1.into_iter().map(joltage::<2>).sum()
-}
+const SAMPLE: &str = "
+        987654321111111
+        811111111111119
+        234234234234278
+        818181911112111";
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u32 = 3;
+    const SAMPLE: Sample = Sample {
+        input: SAMPLE,
+        part1: Some("357"),
+        part2: Some("3121910778619"),
+    };
+
+    /// Each bank with its line, to point at ones too short for part 2.
+    type Input = Vec<(usize, Vec<u32>)>;
+    type Answer1 = u64;
+    type Answer2 = Result<u64, SolveError>;
+
+    fn parse(s: &str) -> Result<Self::Input, ParseError> {
+        parse_all(s, parse)?
+            .into_iter()
+            .map(|(text, bank)| match bank.len() {
+                ..2 => Err(ParseError::at(s, text, "expected at least 2 batteries")),
+                _ => Ok((line_of(s, text), bank)),
+            })
+            .collect()
+    }
+
+    fn part1(banks: &Self::Input) -> u64 {
+        banks.iter().map(|(_, bank)| joltage::<2>(bank)).sum()
+    }
 
-pub fn solve_2(s: &str) -> u64 {
-    parse(s).unwrap().1.into_iter().map(joltage::<12>).sum()
+    fn part2(banks: &Self::Input) -> Option<Result<u64, SolveError>> {
+        let total = banks
+            .iter()
+            .map(|(line, bank)| match bank.len() {
+                ..12 => Err(SolveError::new(format!(
+                    "line {line}: expected at least 12 batteries"
+                ))),
+                _ => Ok(joltage::<12>(bank)),
+            })
+            .sum();
+        Some(total)
+    }
+
+    /// `count` banks of `width` batteries, and never fewer than 12.
+    fn generate(rng: &mut Rng, size: Size) -> Option<String> {
+        let width = size.width_or(100).max(12);
+        let banks = (0..size.count_or(200)).map(|_| {
+            (0..width)
+                .map(|_| char::from_digit(rng.range(1..=9) as u32, 10).unwrap())
+                .collect()
+        });
+        Some(generate::lines(banks))
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use itertools::Itertools;
+    use proptest::prelude::*;
+
     use super::*;
 
-    const SAMPLE: &'static str = "
-        987654321111111
-        811111111111119
-        234234234234278
-        818181911112111";
+    /// Tries every choice of `n` batteries.
+    fn naive(bank: &[u32], n: usize) -> u64 {
+        bank.iter()
+            .combinations(n)
+            .map(|digits| digits.into_iter().fold(0, |acc, &d| acc * 10 + d as u64))
+            .max()
+            .unwrap()
+    }
 
     #[test]
     fn test_sample() {
-        assert_eq!(solve(SAMPLE), 357);
+        assert_eq!(Day3::part1(&Day3::parse(SAMPLE).unwrap()), 357);
     }
 
     #[test]
     fn test_sample_2() {
-        assert_eq!(solve_2(SAMPLE), 3121910778619);
+        assert_eq!(
+            Day3::part2(&Day3::parse(SAMPLE).unwrap()),
+            Some(Ok(3121910778619))
+        );
+    }
+
+    #[test]
+    fn test_short_bank() {
+        let error = Day3::parse("123456789012\n1").err().unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+        let banks = Day3::parse("987654321111\n\n811").unwrap();
+        assert_eq!(Day3::part1(&banks), 98 + 81);
+        let error = SolveError::new("line 3: expected at least 12 batteries");
+        assert_eq!(Day3::part2(&banks), Some(Err(error)));
+    }
+
+    proptest! {
+        #[test]
+        fn test_naive(banks in prop::collection::vec(prop::collection::vec(1..=9u32, 12..16), 1..4)) {
+            let part1 = banks.iter().map(|bank| naive(bank, 2)).sum::<u64>();
+            let part2 = banks.iter().map(|bank| naive(bank, 12)).sum::<u64>();
+            let banks: Vec<_> = banks.into_iter().enumerate().map(|(i, bank)| (i + 1, bank)).collect();
+            prop_assert_eq!(Day3::part1(&banks), part1);
+            prop_assert_eq!(Day3::part2(&banks), Some(Ok(part2)));
+        }
     }
 }
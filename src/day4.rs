@@ -1,60 +1,27 @@
-use std::collections::{HashMap, HashSet};
+use crate::{
+    generate::{self, Rng, Size},
+    solution::{Sample, Solution},
+    utils::{Grid, ParseError},
+};
 
-use itertools::Itertools;
-
-fn parse(s: &str) -> HashSet<(i32, i32)> {
-    let mut paper = HashSet::new();
-    for (y, row) in s.trim().lines().enumerate() {
-        for (x, c) in row.trim().chars().enumerate() {
-            if c == '@' {
-                paper.insert((y as i32, x as i32));
-            }
-        }
-    }
-    paper
+/// Whether each place holds a roll of paper.
+fn parse(s: &str) -> Result<Grid<bool>, ParseError> {
+    Grid::parse(s, |c| match c {
+        '@' => Ok(true),
+        '.' => Ok(false),
+        _ => Err("expected `@` or `.`"),
+    })
 }
 
-fn remove_paper(mut paper: HashSet<(i32, i32)>) -> HashSet<(i32, i32)> {
-    let mut counts: HashMap<(i32, i32), usize> = paper.iter().map(|&p| (p, 0)).collect();
-    for &(y, x) in &paper {
-        for (dy, dx) in (-1..2).cartesian_product(-1..2) {
-            if (dy, dx) != (0, 0) {
-                let p = (y + dy, x + dx);
-                counts.entry(p).and_modify(|c| *c += 1);
-            }
-        }
-    }
-    for (p, c) in counts {
-        if c < 4 {
-            paper.remove(&p);
-        }
-    }
+/// Rolls with fewer than four rolls around them.
+fn accessible(paper: &Grid<bool>) -> Vec<(usize, usize)> {
     paper
+        .positions()
+        .filter(|&p| paper[p] && paper.neighbours8(p).filter(|&n| paper[n]).count() < 4)
+        .collect()
 }
 
-pub fn solve(s: &str) -> usize {
-    let paper = parse(s);
-    paper.len() - remove_paper(paper).len()
-}
-
-pub fn solve_2(s: &str) -> usize {
-    let mut paper = parse(s);
-    let original_len = paper.len();
-    loop {
-        let last_len = paper.len();
-        paper = remove_paper(paper);
-        if paper.len() == last_len {
-            break;
-        }
-    }
-    original_len - paper.len()
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    const SAMPLE: &'static str = "
+const SAMPLE: &str = "
         ..@@.@@@@.
         @@@.@.@.@@
         @@@@@.@.@@
@@ -66,13 +33,118 @@ mod test {
         .@@@@@@@@.
         @.@.@@@.@.";
 
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u32 = 4;
+    const SAMPLE: Sample = Sample {
+        input: SAMPLE,
+        part1: Some("13"),
+        part2: Some("43"),
+    };
+
+    type Input = Grid<bool>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(s: &str) -> Result<Self::Input, ParseError> {
+        parse(s)
+    }
+
+    fn part1(paper: &Self::Input) -> usize {
+        accessible(paper).len()
+    }
+
+    fn part2(paper: &Self::Input) -> Option<usize> {
+        let mut paper = paper.clone();
+        let mut removed = 0;
+        loop {
+            let rolls = accessible(&paper);
+            if rolls.is_empty() {
+                break;
+            }
+            removed += rolls.len();
+            rolls.into_iter().for_each(|p| paper[p] = false);
+        }
+        Some(removed)
+    }
+
+    /// `count` rows of `width` places, two thirds of them rolls of paper.
+    fn generate(rng: &mut Rng, size: Size) -> Option<String> {
+        let width = size.width_or(140);
+        let rows = (0..size.count_or(140)).map(|_| {
+            (0..width)
+                .map(|_| if rng.chance(0.65) { '@' } else { '.' })
+                .collect()
+        });
+        Some(generate::lines(rows))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn accessible(grid: &[Vec<bool>], y: usize, x: usize) -> bool {
+        let neighbours = (y.saturating_sub(1)..=y + 1)
+            .flat_map(|ny| (x.saturating_sub(1)..=x + 1).map(move |nx| (ny, nx)))
+            .filter(|&p| p != (y, x))
+            .filter(|&(ny, nx)| grid.get(ny).and_then(|row| row.get(nx)) == Some(&true))
+            .count();
+        grid[y][x] && neighbours < 4
+    }
+
+    /// Removes accessible rolls one at a time, scanning the whole grid for each.
+    fn naive(mut grid: Vec<Vec<bool>>) -> (usize, usize) {
+        let cells: Vec<_> = (0..grid.len())
+            .flat_map(|y| (0..grid[y].len()).map(move |x| (y, x)))
+            .collect();
+        let first = cells
+            .iter()
+            .filter(|&&(y, x)| accessible(&grid, y, x))
+            .count();
+        let mut removed = 0;
+        while let Some(&(y, x)) = cells.iter().find(|&&(y, x)| accessible(&grid, y, x)) {
+            grid[y][x] = false;
+            removed += 1;
+        }
+        (first, removed)
+    }
+
+    fn render(grid: &[Vec<bool>]) -> String {
+        grid.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&c| if c { '@' } else { '.' })
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+
     #[test]
     fn test_sample() {
-        assert_eq!(solve(SAMPLE), 13);
+        assert_eq!(Day4::part1(&Day4::parse(SAMPLE).unwrap()), 13);
     }
 
     #[test]
     fn test_sample_2() {
-        assert_eq!(solve_2(SAMPLE), 43);
+        assert_eq!(Day4::part2(&Day4::parse(SAMPLE).unwrap()), Some(43));
+    }
+
+    proptest! {
+        #[test]
+        fn test_naive(
+            grid in (1..12usize, 1..12usize).prop_flat_map(|(h, w)| {
+                prop::collection::vec(prop::collection::vec(prop::bool::weighted(0.7), w), h)
+            })
+        ) {
+            let paper = Day4::parse(&render(&grid)).unwrap();
+            let (part1, part2) = naive(grid);
+            prop_assert_eq!(Day4::part1(&paper), part1);
+            prop_assert_eq!(Day4::part2(&paper), Some(part2));
+        }
     }
 }
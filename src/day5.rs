@@ -1,47 +1,28 @@
 use nom::{
-    bytes::complete::tag,
-    character::complete::{digit1, multispace0},
-    combinator::map_res,
-    multi::many0,
-    sequence::{pair, preceded, separated_pair},
+    character::complete::char,
+    combinator::consumed,
+    sequence::{pair, separated_pair},
     IResult,
 };
 
-fn parse(s: &str) -> IResult<&str, (Vec<(u64, u64)>, Vec<u64>)> {
-    let num = || map_res(digit1, str::parse);
-    let range = separated_pair(num(), tag("-"), num());
-    let fresh = many0(preceded(multispace0, range));
-    let ids = many0(preceded(multispace0, num()));
-    pair(fresh, ids)(s)
-}
+use crate::{
+    generate::{self, Rng, Size},
+    solution::{Sample, Solution},
+    utils::{
+        parse::{lines_of, unsigned},
+        parse_all, IntervalSet, ParseError, SolveError, Spanned,
+    },
+};
 
-pub fn solve(s: &str) -> usize {
-    let (fresh, ids) = parse(s).unwrap().1;
-    ids.iter()
-        .filter(|id| fresh.iter().any(|(l, h)| (l..=h).contains(id)))
-        .count()
-}
+type Range = (u64, u64);
+type Input = (IntervalSet<u64>, Vec<u64>);
 
-pub fn solve_2(s: &str) -> u64 {
-    let (mut ranges, _) = parse(s).unwrap().1;
-    ranges.sort();
-    let mut i = 0;
-    while i + 1 < ranges.len() {
-        if ranges[i].1 < ranges[i + 1].0 {
-            i += 1;
-        } else {
-            ranges[i].1 = u64::max(ranges[i].1, ranges[i + 1].1);
-            ranges.remove(i + 1);
-        }
-    }
-    ranges.into_iter().map(|(i, j)| j - i + 1).sum()
+fn parse(s: &str) -> IResult<&str, (Vec<Spanned<'_, Range>>, Vec<u64>)> {
+    let range = consumed(separated_pair(unsigned, char('-'), unsigned));
+    pair(lines_of(range), lines_of(unsigned))(s)
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    const SAMPLE: &'static str = "
+const SAMPLE: &str = "
         3-5
         10-14
         16-20
@@ -54,13 +35,108 @@ mod test {
         17
         32";
 
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u32 = 5;
+    const SAMPLE: Sample = Sample {
+        input: SAMPLE,
+        part1: Some("3"),
+        part2: Some("14"),
+    };
+
+    type Input = Input;
+    type Answer1 = usize;
+    type Answer2 = Result<u64, SolveError>;
+
+    fn parse(s: &str) -> Result<Self::Input, ParseError> {
+        let (fresh, ids) = parse_all(s, parse)?;
+        let fresh = fresh
+            .into_iter()
+            .map(|(text, (l, h))| match l <= h {
+                true => Ok(l..=h),
+                false => Err(ParseError::at(s, text, "range ends before it starts")),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok((fresh, ids))
+    }
+
+    fn part1((fresh, ids): &Self::Input) -> usize {
+        ids.iter().filter(|&&id| fresh.contains(id)).count()
+    }
+
+    fn part2((fresh, _): &Self::Input) -> Option<Result<u64, SolveError>> {
+        let count = fresh.len();
+        Some(count.ok_or_else(|| SolveError::new("too many fresh IDs to count")))
+    }
+
+    /// `count` ranges, some overlapping, and five times as many IDs, all
+    /// with up to `width` digits.
+    fn generate(rng: &mut Rng, size: Size) -> Option<String> {
+        let max = 10u64.pow(size.width_or(15).clamp(1, 19) as u32) - 1;
+        let count = size.count_or(180);
+        let ranges: Vec<_> = (0..count)
+            .map(|_| {
+                let l = rng.range(1..=max);
+                let len = rng.range(0..=max / count.max(1) as u64);
+                (l, l.saturating_add(len).min(max))
+            })
+            .collect();
+        let ids = (0..5 * count).map(|_| match rng.chance(0.5) {
+            true => {
+                let &(l, h) = rng.choose(&ranges);
+                rng.range(l..=h)
+            }
+            false => rng.range(1..=max),
+        });
+        let ranges = ranges.iter().map(|(l, h)| format!("{l}-{h}"));
+        let ids = ids.map(|id| id.to_string());
+        let blank = std::iter::once(String::new());
+        Some(generate::lines(ranges.chain(blank).chain(ids)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn ranges() -> impl Strategy<Value = Vec<(u64, u64)>> {
+        prop::collection::vec(
+            (0..200u64, 0..30u64).prop_map(|(l, len)| (l, l + len)),
+            0..10,
+        )
+    }
+
     #[test]
     fn test_sample() {
-        assert_eq!(solve(SAMPLE), 3);
+        assert_eq!(Day5::part1(&Day5::parse(SAMPLE).unwrap()), 3);
     }
 
     #[test]
     fn test_sample_2() {
-        assert_eq!(solve_2(SAMPLE), 14);
+        assert_eq!(Day5::part2(&Day5::parse(SAMPLE).unwrap()), Some(Ok(14)));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = Day5::parse("3-5\n9-7\n\n4").err().unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+        let every_id = Day5::parse("0-18446744073709551615").unwrap();
+        assert!(matches!(Day5::part2(&every_id), Some(Err(_))));
+    }
+
+    proptest! {
+        #[test]
+        fn test_naive(ranges in ranges(), ids in prop::collection::vec(0..250u64, 0..20)) {
+            let fresh: HashSet<_> = ranges.iter().flat_map(|&(l, h)| l..=h).collect();
+            let part1 = ids.iter().filter(|id| fresh.contains(id)).count();
+            let input = (ranges.iter().map(|&(l, h)| l..=h).collect(), ids);
+            prop_assert_eq!(Day5::part1(&input), part1);
+            prop_assert_eq!(Day5::part2(&input), Some(Ok(fresh.len() as u64)));
+        }
     }
 }
@@ -1,77 +1,315 @@
 use std::iter::zip;
 
 use nom::{
-    character::complete::{digit1, multispace0, satisfy, space0},
-    combinator::map_res,
-    multi::{many0, many1},
+    character::complete::{multispace0, satisfy},
+    combinator::consumed,
     sequence::{pair, preceded},
     IResult,
 };
 
-fn parse(s: &str) -> IResult<&str, (Vec<Vec<u64>>, Vec<char>)> {
-    let num = || preceded(space0, map_res(digit1, str::parse));
-    let nums = many0(preceded(multispace0, many1(num())));
-    let op = preceded(space0, satisfy(|c| c == '*' || c == '+'));
-    let ops = preceded(multispace0, many1(op));
-    pair(nums, ops)(s)
+use crate::{
+    generate::{self, Rng, Size},
+    solution::{Sample, Solution},
+    utils::{
+        parse::{lines_of, spaced, unsigned},
+        parse_all, ParseError, SolveError, Spanned,
+    },
+};
+
+pub struct Worksheet {
+    nums: Vec<Vec<u64>>,
+    ops: Vec<char>,
+    /// The raw characters of the rows of numbers, since part 2 reads them
+    /// column by column.
+    chars: Vec<Vec<char>>,
+    /// The column of each operator, which is the first of its problem.
+    op_columns: Vec<usize>,
 }
 
-fn calculate(nums: Vec<Vec<u64>>, ops: Vec<char>) -> u64 {
-    zip(ops, nums)
-        .map(|(op, nums)| match op {
-            '*' => nums.iter().fold(1, |acc, n| acc * n),
-            '+' => nums.iter().fold(0, |acc, n| acc + n),
-            _ => panic!(),
-        })
-        .sum()
+/// The rows of numbers, then the row of operators.
+type Rows<'a> = (Vec<Spanned<'a, Vec<u64>>>, Spanned<'a, Vec<char>>);
+
+fn parse(s: &str) -> IResult<&str, Rows<'_>> {
+    let nums = lines_of(consumed(spaced(unsigned)));
+    let ops = consumed(spaced(satisfy(|c| c == '*' || c == '+')));
+    pair(nums, preceded(multispace0, ops))(s)
 }
 
-pub fn solve(s: &str) -> u64 {
-    let (nums, ops) = parse(s).unwrap().1;
-    let mut nums_t = Vec::new();
-    for x in 0..nums[0].len() {
-        nums_t.push(vec![]);
-        for y in 0..nums.len() {
-            nums_t.last_mut().unwrap().push(nums[y][x]);
-        }
+fn calculate(nums: Vec<Vec<u64>>, ops: &[char]) -> Result<u64, SolveError> {
+    if nums.len() != ops.len() {
+        let message = format!("{} problems for {} operators", nums.len(), ops.len());
+        return Err(SolveError::new(message));
     }
-    calculate(nums_t, ops)
+    let overflow = || SolveError::new("grand total overflows");
+    zip(ops, nums).try_fold(0u64, |total, (op, nums)| {
+        let result = match op {
+            '*' => nums.iter().try_fold(1u64, |acc, &n| acc.checked_mul(n)),
+            '+' => nums.iter().try_fold(0u64, |acc, &n| acc.checked_add(n)),
+            _ => return Err(SolveError::new(format!("unknown operator {op:?}"))),
+        };
+        total
+            .checked_add(result.ok_or_else(overflow)?)
+            .ok_or_else(overflow)
+    })
 }
 
-pub fn solve_2(s: &str) -> u64 {
-    let (_, ops) = parse(s).unwrap().1;
-    let s: Vec<Vec<_>> = s.lines().map(|l| l.chars().collect()).collect();
-    let mut nums: Vec<Vec<u64>> = vec![Vec::new()];
-    for x in 0..s[0].len() {
-        let Some(num) = (0..s.len() - 1)
-            .filter_map(|y| s[y][x].to_digit(10).map(|d| d as u64))
-            .reduce(|acc, d| acc * 10 + d)
-        else {
-            nums.push(Vec::new());
-            continue;
-        };
-        nums.last_mut().unwrap().push(num);
+const SAMPLE: &str = "\
+123 328  51 64 
+ 45 64  387 23 
+  6 98  215 314
+*   +   *   +  ";
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u32 = 6;
+    const SAMPLE: Sample = Sample {
+        input: SAMPLE,
+        part1: Some("4277556"),
+        part2: Some("3263827"),
+    };
+
+    type Input = Worksheet;
+    type Answer1 = Result<u64, SolveError>;
+    type Answer2 = Result<u64, SolveError>;
+
+    fn parse(s: &str) -> Result<Self::Input, ParseError> {
+        let (nums, (ops_text, ops)) = parse_all(s, parse)?;
+        if nums.is_empty() {
+            return Err(ParseError::at(s, ops_text, "expected numbers above"));
+        }
+        if let Some((text, _)) = nums.iter().find(|(_, row)| row.len() != ops.len()) {
+            let message = format!("expected {} numbers, one per operator", ops.len());
+            return Err(ParseError::at(s, text, message));
+        }
+        let nums = nums.into_iter().map(|(_, row)| row).collect();
+        // Blank lines and indentation are allowed, so the columns are read
+        // off the raw lines that hold something.
+        let mut chars: Vec<Vec<char>> = s
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.chars().collect())
+            .collect();
+        let op_columns = chars
+            .pop()
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, c)| !c.is_whitespace())
+            .map(|(x, _)| x)
+            .collect();
+        Ok(Worksheet {
+            nums,
+            ops,
+            chars,
+            op_columns,
+        })
+    }
+
+    fn part1(sheet: &Self::Input) -> Result<u64, SolveError> {
+        let nums = &sheet.nums;
+        let mut nums_t = Vec::new();
+        for x in 0..nums[0].len() {
+            nums_t.push(vec![]);
+            for row in nums {
+                nums_t.last_mut().unwrap().push(row[x]);
+            }
+        }
+        calculate(nums_t, &sheet.ops)
+    }
+
+    fn part2(sheet: &Self::Input) -> Option<Result<u64, SolveError>> {
+        let s = &sheet.chars;
+        // Lines may be ragged where trailing spaces were trimmed.
+        let width = s.iter().map(Vec::len).max().unwrap_or(0);
+        let mut ends = sheet.op_columns.iter().skip(1).copied();
+        let mut nums = Vec::new();
+        for (i, &start) in sheet.op_columns.iter().enumerate() {
+            let end = ends.next().unwrap_or(width);
+            let mut problem = Vec::new();
+            for x in start..end {
+                let digits: Vec<u64> = s
+                    .iter()
+                    .filter_map(|row| row.get(x)?.to_digit(10).map(u64::from))
+                    .collect();
+                // The blank column between problems has no digits.
+                if digits.is_empty() {
+                    continue;
+                }
+                let num = digits
+                    .into_iter()
+                    .try_fold(0u64, |acc, d| acc.checked_mul(10)?.checked_add(d));
+                let Some(num) = num else {
+                    return Some(Err(SolveError::new("number too large")));
+                };
+                problem.push(num);
+            }
+            if problem.is_empty() {
+                let message = format!("problem {}: no numbers above its operator", i + 1);
+                return Some(Err(SolveError::new(message)));
+            }
+            nums.push(problem);
+        }
+        Some(calculate(nums, &sheet.ops))
+    }
+
+    /// `count` problems of `width` numbers, each of up to `width` digits.
+    fn generate(rng: &mut Rng, size: Size) -> Option<String> {
+        let height = size.width_or(4).max(1);
+        let mut lines = vec![Vec::new(); height + 1];
+        for _ in 0..size.count_or(1000) {
+            // One number spans the whole block, so that part 2 finds no blank
+            // column inside a problem, and the rest line up on one side.
+            let width = rng.range(1..=height as u64) as usize;
+            let full = rng.index(height);
+            let right = rng.chance(0.5);
+            for (y, line) in lines[..height].iter_mut().enumerate() {
+                let len = match y == full {
+                    true => width,
+                    false => rng.range(1..=width as u64) as usize,
+                };
+                let num: String = (0..len)
+                    .map(|_| char::from_digit(rng.range(1..=9) as u32, 10).unwrap())
+                    .collect();
+                line.push(match right {
+                    true => format!("{num:>width$}"),
+                    false => format!("{num:<width$}"),
+                });
+            }
+            let op = rng.choose(&['*', '+']);
+            lines[height].push(format!("{op:<width$}"));
+        }
+        Some(generate::lines(lines.iter().map(|line| line.join(" "))))
     }
-    calculate(nums, ops)
 }
 
 #[cfg(test)]
 mod test {
+    use proptest::prelude::*;
+
     use super::*;
 
-    const SAMPLE: &'static str = "\
-123 328  51 64 
- 45 64  387 23 
-  6 98  215 314
-*   +   *   +  ";
+    /// One problem as a block of digits or blanks, with its operator.
+    type Problem = (Vec<Vec<Option<u32>>>, char);
+
+    /// Each row of a problem holds a run of digits, the first one spanning the
+    /// whole block so that no column is blank.
+    fn problem(height: usize) -> impl Strategy<Value = Problem> {
+        (1..5usize, prop::sample::select(vec!['*', '+'])).prop_flat_map(move |(width, op)| {
+            let row = (0..width, 0..width).prop_flat_map(move |(a, b)| {
+                let (a, b) = (a.min(b), a.max(b));
+                prop::collection::vec(0..10u32, b - a + 1).prop_map(move |digits| {
+                    let mut row = vec![None; width];
+                    for (x, d) in (a..=b).zip(digits) {
+                        row[x] = Some(d);
+                    }
+                    row
+                })
+            });
+            let first = prop::collection::vec(0..10u32, width)
+                .prop_map(|digits| digits.into_iter().map(Some).collect::<Vec<_>>());
+            (first, prop::collection::vec(row, height - 1)).prop_map(move |(first, rest)| {
+                let mut rows = vec![first];
+                rows.extend(rest);
+                (rows, op)
+            })
+        })
+    }
+
+    fn number(digits: impl Iterator<Item = Option<u32>>) -> u64 {
+        digits.flatten().fold(0, |acc, d| acc * 10 + d as u64)
+    }
+
+    fn apply(op: char, nums: impl Iterator<Item = u64>) -> u64 {
+        match op {
+            '*' => nums.product(),
+            _ => nums.sum(),
+        }
+    }
+
+    /// Problems read row by row, and then column by column.
+    fn naive(problems: &[Problem]) -> (u64, u64) {
+        let by_rows = problems
+            .iter()
+            .map(|(rows, op)| apply(*op, rows.iter().map(|row| number(row.iter().copied()))))
+            .sum();
+        let by_columns = problems
+            .iter()
+            .map(|(rows, op)| {
+                let columns = (0..rows[0].len()).map(|x| number(rows.iter().map(|row| row[x])));
+                apply(*op, columns)
+            })
+            .sum();
+        (by_rows, by_columns)
+    }
+
+    /// Lays out problems of the same height side by side, one blank column apart.
+    fn render(problems: &[Problem]) -> String {
+        let height = problems[0].0.len();
+        let mut lines = vec![Vec::new(); height + 1];
+        for (rows, op) in problems {
+            for (line, row) in lines.iter_mut().zip(rows) {
+                let row: String = row
+                    .iter()
+                    .map(|d| d.map_or(' ', |d| char::from_digit(d, 10).unwrap()))
+                    .collect();
+                line.push(row);
+            }
+            let width = rows[0].len();
+            lines[height].push(format!("{op:<width$}"));
+        }
+        let lines: Vec<_> = lines.iter().map(|line| line.join(" ")).collect();
+        lines.join("\n")
+    }
 
     #[test]
     fn test_sample() {
-        assert_eq!(solve(SAMPLE), 4277556);
+        assert_eq!(Day6::part1(&Day6::parse(SAMPLE).unwrap()), Ok(4277556));
     }
 
     #[test]
     fn test_sample_2() {
-        assert_eq!(solve_2(SAMPLE), 3263827);
+        assert_eq!(
+            Day6::part2(&Day6::parse(SAMPLE).unwrap()),
+            Some(Ok(3263827))
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = Day6::parse("1 2\n3\n* +").err().unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert!(Day6::parse("* +").is_err());
+        let error = Day6::parse("1 2\n3 4\n").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 1: unexpected end of input at end of line"
+        );
+        let sheet = Day6::parse("4294967296\n4294967296\n*").unwrap();
+        assert!(Day6::part1(&sheet).is_err());
+        // Digits left of the first operator belong to no problem.
+        let sheet = Day6::parse("  1 2\n* +").unwrap();
+        assert!(matches!(Day6::part2(&sheet), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_indented() {
+        let sheet = Day6::parse("\n  1 2\n  3 4\n\n  * +\n").unwrap();
+        assert_eq!(Day6::part1(&sheet), Ok(3 + 6));
+        assert_eq!(Day6::part2(&sheet), Some(Ok(13 + 24)));
+    }
+
+    proptest! {
+        #[test]
+        fn test_naive(
+            problems in (1..5usize).prop_flat_map(|height| {
+                prop::collection::vec(problem(height), 1..5)
+            }),
+        ) {
+            let sheet = Day6::parse(&render(&problems)).unwrap();
+            let (part1, part2) = naive(&problems);
+            prop_assert_eq!(Day6::part1(&sheet), Ok(part1));
+            prop_assert_eq!(Day6::part2(&sheet), Some(Ok(part2)));
+        }
     }
 }
@@ -1,72 +1,51 @@
-use std::collections::{HashMap, HashSet};
-
-fn parse(s: &str) -> ((i32, i32), HashSet<(i32, i32)>) {
-    let mut splitters = HashSet::new();
-    let mut start = None;
-    for (y, line) in s.trim().lines().enumerate() {
-        for (x, c) in line.trim().chars().enumerate() {
-            match c {
-                '^' => {
-                    splitters.insert((y as i32, x as i32));
-                }
-                'S' => start = Some((y as i32, x as i32)),
-                '.' => continue,
-                _ => panic!(),
-            }
-        }
-    }
-    (start.unwrap(), splitters)
-}
+use std::collections::HashSet;
 
-pub fn solve(s: &str) -> usize {
-    let (start, splitters) = parse(s);
-    let &max_y = splitters.iter().map(|(y, _)| y).max().unwrap();
-    let mut beams: HashSet<_> = [start.1].into_iter().collect();
-    let mut splits = 0;
-    for y in 1..=max_y {
-        let mut new_beams = HashSet::new();
-        for x in beams {
-            if splitters.contains(&(y, x)) {
-                splits += 1;
-                new_beams.extend([x - 1, x + 1]);
-            } else {
-                new_beams.insert(x);
-            }
-        }
-        beams = new_beams;
-    }
-    splits
+use crate::{
+    generate::{self, Rng, Size},
+    solution::{Sample, Solution},
+    utils::{Grid, ParseError, SolveError},
+};
+
+/// The start position and the map of splitters.
+type Manifold = ((usize, usize), Grid<char>);
+
+fn parse(s: &str) -> Result<Manifold, ParseError> {
+    let grid = Grid::parse(s, |c| match c {
+        '^' | 'S' | '.' => Ok(c),
+        _ => Err("expected `^`, `S` or `.`"),
+    })?;
+    let start = grid.iter().find(|&(_, &c)| c == 'S').map(|(p, _)| p);
+    let start = start.ok_or_else(|| ParseError::at(s, s.trim(), "missing start `S`"))?;
+    Ok((start, grid))
 }
 
-pub fn solve_2(s: &str) -> usize {
-    let (start, splitters) = parse(s);
-    let &max_y = splitters.iter().map(|(y, _)| y).max().unwrap();
-    let &max_x = splitters.iter().map(|(_, x)| x).max().unwrap();
-    let mut counts: HashMap<(i32, i32), usize> = [(start, 1)].into_iter().collect();
-    for y in 1..=max_y {
-        for x in -1..=max_x + 1 {
-            if splitters.contains(&(y, x)) {
-                continue;
+fn timelines(start: (usize, usize), grid: &Grid<char>) -> Result<usize, SolveError> {
+    let overflow = || SolveError::new("too many timelines to count");
+    // Timelines per column, with one more on either side for those that
+    // leave the grid, so `counts[x + 1]` is for column `x`.
+    let mut counts = vec![0usize; grid.width() + 2];
+    counts[start.1 + 1] = 1;
+    for row in grid.rows().skip(start.0 + 1) {
+        let mut new_counts = vec![0usize; counts.len()];
+        for (i, &count) in counts.iter().enumerate() {
+            let split = i.checked_sub(1).and_then(|x| row.get(x)) == Some(&'^');
+            let targets = match split {
+                true => [Some(i - 1), Some(i + 1)],
+                false => [Some(i), None],
+            };
+            for j in targets.into_iter().flatten() {
+                new_counts[j] = new_counts[j].checked_add(count).ok_or_else(overflow)?;
             }
-            let n: usize = [x - 1, x + 1]
-                .into_iter()
-                .filter(|&x| splitters.contains(&(y, x)))
-                .chain([x])
-                .filter_map(|x| counts.get(&(y - 1, x)))
-                .sum();
-            counts.insert((y, x), n);
         }
+        counts = new_counts;
     }
-    (-1..=max_x + 1)
-        .filter_map(|x| counts.get(&(max_y, x)))
-        .sum()
+    counts
+        .into_iter()
+        .try_fold(0usize, |total, count| total.checked_add(count))
+        .ok_or_else(overflow)
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    const SAMPLE: &'static str = "
+const SAMPLE: &str = "
         .......S.......
         ...............
         .......^.......
@@ -84,13 +63,155 @@ mod test {
         .^.^.^.^.^...^.
         ...............";
 
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u32 = 7;
+    const SAMPLE: Sample = Sample {
+        input: SAMPLE,
+        part1: Some("21"),
+        part2: Some("40"),
+    };
+
+    type Input = Manifold;
+    type Answer1 = usize;
+    type Answer2 = Result<usize, SolveError>;
+
+    fn parse(s: &str) -> Result<Self::Input, ParseError> {
+        parse(s)
+    }
+
+    fn part1((start, grid): &Self::Input) -> usize {
+        let mut beams: HashSet<_> = [start.1].into_iter().collect();
+        let mut splits = 0;
+        for row in grid.rows().skip(start.0 + 1) {
+            let mut new_beams = HashSet::new();
+            for x in beams {
+                if row[x] == '^' {
+                    splits += 1;
+                    // Beams that leave the grid have no splitters left to hit.
+                    new_beams.extend(x.checked_sub(1));
+                    new_beams.extend(Some(x + 1).filter(|&x| x < row.len()));
+                } else {
+                    new_beams.insert(x);
+                }
+            }
+            beams = new_beams;
+        }
+        splits
+    }
+
+    fn part2((start, grid): &Self::Input) -> Option<Result<usize, SolveError>> {
+        Some(timelines(*start, grid))
+    }
+
+    /// `count` rows of `width` places, with splitters on every other row
+    /// where the beams from the start could reach them.
+    fn generate(rng: &mut Rng, size: Size) -> Option<String> {
+        let width = size.width_or(141).max(1);
+        let start = width / 2;
+        let rows = (0..size.count_or(142)).map(|y| {
+            // Beams spread by a place on each side per splitter row.
+            let reach = if y % 2 == 0 { y / 2 } else { 0 };
+            (0..width)
+                .map(|x| {
+                    let d = x.abs_diff(start);
+                    match x {
+                        _ if y == 0 && x == start => 'S',
+                        // Splitters stay off the sides, so that no beam leaves the grid.
+                        _ if x == 0 || x + 1 == width => '.',
+                        _ if d < reach && (reach - d) % 2 == 1 && rng.chance(0.7) => '^',
+                        _ => '.',
+                    }
+                })
+                .collect()
+        });
+        Some(generate::lines(rows))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// Follows every timeline separately, returning how many leave the bottom
+    /// row and adding each splitter hit on the way to `hit`.
+    fn follow(
+        grid: &[Vec<char>],
+        (y, x): (usize, usize),
+        hit: &mut HashSet<(usize, usize)>,
+    ) -> usize {
+        if y + 1 == grid.len() {
+            return 1;
+        }
+        match grid[y + 1][x] {
+            '^' => {
+                hit.insert((y + 1, x));
+                follow(grid, (y + 1, x - 1), hit) + follow(grid, (y + 1, x + 1), hit)
+            }
+            _ => follow(grid, (y + 1, x), hit),
+        }
+    }
+
+    fn naive(grid: &[Vec<char>]) -> (usize, usize) {
+        let x = grid[0].iter().position(|&c| c == 'S').unwrap();
+        let mut hit = HashSet::new();
+        let timelines = follow(grid, (0, x), &mut hit);
+        (hit.len(), timelines)
+    }
+
+    /// A manifold like the puzzle's: `S` on top, splitters on every other row
+    /// at least two apart and clear of the edges, and one right below `S`.
+    fn manifold() -> impl Strategy<Value = Vec<Vec<char>>> {
+        (2..6usize, 3..9usize).prop_flat_map(|(layers, half_width)| {
+            let width = 2 * half_width + 1;
+            let row = prop::collection::vec(prop::bool::weighted(0.4), half_width - 1);
+            (1..half_width, prop::collection::vec(row, layers)).prop_map(move |(start, layers)| {
+                let mut grid = vec![vec!['.'; width]];
+                grid[0][2 * start] = 'S';
+                for (i, splitters) in layers.into_iter().enumerate() {
+                    let mut row = vec!['.'; width];
+                    for (j, splitter) in splitters.into_iter().enumerate() {
+                        if splitter || (i == 0 && j + 1 == start) {
+                            row[2 * j + 2] = '^';
+                        }
+                    }
+                    grid.push(vec!['.'; width]);
+                    grid.push(row);
+                }
+                grid.push(vec!['.'; width]);
+                grid
+            })
+        })
+    }
+
     #[test]
     fn test_sample() {
-        assert_eq!(solve(SAMPLE), 21);
+        assert_eq!(Day7::part1(&Day7::parse(SAMPLE).unwrap()), 21);
     }
 
     #[test]
     fn test_sample_2() {
-        assert_eq!(solve_2(SAMPLE), 40);
+        assert_eq!(Day7::part2(&Day7::parse(SAMPLE).unwrap()), Some(Ok(40)));
+    }
+
+    #[test]
+    fn test_no_splitters() {
+        let manifold = Day7::parse("..S..\n.....").unwrap();
+        assert_eq!(Day7::part1(&manifold), 0);
+        assert_eq!(Day7::part2(&manifold), Some(Ok(1)));
+    }
+
+    proptest! {
+        #[test]
+        fn test_naive(grid in manifold()) {
+            let text: Vec<String> = grid.iter().map(|row| row.iter().collect()).collect();
+            let manifold = Day7::parse(&text.join("\n")).unwrap();
+            let (part1, part2) = naive(&grid);
+            prop_assert_eq!(Day7::part1(&manifold), part1);
+            prop_assert_eq!(Day7::part2(&manifold), Some(Ok(part2)));
+        }
     }
 }
@@ -1,112 +1,50 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
 
 use itertools::Itertools;
 use nom::{
-    bytes::complete::tag,
-    character::complete::{digit1, multispace0},
-    combinator::map_res,
-    multi::many0,
-    sequence::{preceded, tuple},
+    combinator::{consumed, map},
     IResult,
 };
 
-#[derive(Clone, Copy)]
-enum Node {
-    Root { size: usize },
-    Child { parent: usize },
-}
-
-struct UnionFind {
-    items: HashMap<(i64, i64, i64), usize>,
-    nodes: Vec<Node>,
-}
-
-impl UnionFind {
-    fn new(items: impl IntoIterator<Item = (i64, i64, i64)>) -> Self {
-        let items: HashMap<_, _> = items.into_iter().enumerate().map(|(i, v)| (v, i)).collect();
-        Self {
-            nodes: vec![Node::Root { size: 1 }; items.len()],
-            items,
-        }
-    }
-
-    fn find(&self, mut i: usize) -> (usize, usize) {
-        loop {
-            match self.nodes[i] {
-                Node::Root { size } => return (i, size),
-                Node::Child { parent } => i = parent,
-            }
-        }
-    }
+use crate::{
+    generate::{self, Rng, Size},
+    solution::{Sample, Solution},
+    utils::{
+        parse::{coords, lines_of, unsigned},
+        parse_all, ParseError, SolveError, Spanned, UnionFind,
+    },
+};
 
-    fn merge(&mut self, a: (i64, i64, i64), b: (i64, i64, i64)) -> Option<bool> {
-        let &a = self.items.get(&a)?;
-        let &b = self.items.get(&b)?;
-        let (a, a_size) = self.find(a);
-        let (b, b_size) = self.find(b);
-        if a == b {
-            return Some(false);
-        }
-        let (parent, child) = if a_size > b_size { (a, b) } else { (b, a) };
-        self.nodes[child] = Node::Child { parent };
-        self.nodes[parent] = Node::Root {
-            size: a_size + b_size,
-        };
-        Some(true)
-    }
+type JunctionBox = (i64, i64, i64);
 
-    fn iter_unions(&self) -> impl Iterator<Item = usize> + '_ {
-        self.nodes.iter().filter_map(|n| match n {
-            Node::Root { size } => Some(*size),
-            Node::Child { .. } => None,
-        })
-    }
+fn parse(s: &str) -> IResult<&str, Vec<Spanned<'_, JunctionBox>>> {
+    // Coordinates that fit in an `i32` keep squared distances within a `u64`.
+    let num = map(unsigned::<i32>, i64::from);
+    let coord = map(coords(num), |[x, y, z]| (x, y, z));
+    lines_of(consumed(coord))(s)
 }
 
-fn parse(s: &str) -> IResult<&str, Vec<(i64, i64, i64)>> {
-    let num = || map_res(digit1, str::parse);
-    let coord = tuple((num(), preceded(tag(","), num()), preceded(tag(","), num())));
-    many0(preceded(multispace0, coord))(s)
+/// The squared distance between two boxes.
+fn distance((x1, y1, z1): (i64, i64, i64), (x2, y2, z2): (i64, i64, i64)) -> u64 {
+    x1.abs_diff(x2).pow(2) + y1.abs_diff(y2).pow(2) + z1.abs_diff(z2).pow(2)
 }
 
-fn run(s: &str, n_connections: usize) -> usize {
-    let items = parse(s).unwrap().1;
-    let mut uf = UnionFind::new(items.clone());
-    let mut pairs: Vec<(_, _)> = items.into_iter().tuple_combinations().collect();
-    pairs.sort_by_key(|&((x1, y1, z1), (x2, y2, z2))| {
-        (x1 - x2).pow(2) + (y1 - y2).pow(2) + (z1 - z2).pow(2)
-    });
-    for &(a, b) in &pairs[0..n_connections] {
-        uf.merge(a, b);
-    }
-    uf.iter_unions().sorted().rev().take(3).product()
+/// Pairs of boxes by index, closest first.
+fn closest_pairs(items: &[(i64, i64, i64)]) -> Vec<(usize, usize)> {
+    let mut pairs: Vec<_> = (0..items.len()).tuple_combinations().collect();
+    pairs.sort_by_key(|&(a, b)| distance(items[a], items[b]));
+    pairs
 }
 
-pub fn solve(s: &str) -> usize {
-    run(s, 1000)
-}
-
-pub fn solve_2(s: &str) -> i64 {
-    let items = parse(s).unwrap().1;
-    let mut uf = UnionFind::new(items.clone());
-    let mut pairs: Vec<(_, _)> = items.into_iter().tuple_combinations().collect();
-    pairs.sort_by_key(|&((x1, y1, z1), (x2, y2, z2))| {
-        (x1 - x2).pow(2) + (y1 - y2).pow(2) + (z1 - z2).pow(2)
-    });
-    let mut last = None;
-    for (a, b) in pairs {
-        if uf.merge(a, b) == Some(true) {
-            last = Some(a.0 * b.0);
-        }
+fn run(items: &[(i64, i64, i64)], n_connections: usize) -> usize {
+    let mut circuits = UnionFind::new(items.len());
+    for (a, b) in closest_pairs(items).into_iter().take(n_connections) {
+        circuits.merge(a, b);
     }
-    last.unwrap()
+    circuits.sizes().sorted().rev().take(3).product()
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    const SAMPLE: &'static str = "
+const SAMPLE: &str = "
         162,817,812
         57,618,57
         906,360,560
@@ -128,13 +66,165 @@ mod test {
         984,92,344
         425,690,689";
 
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u32 = 8;
+    const SAMPLE: Sample = Sample {
+        input: SAMPLE,
+        // The example connects 10 pairs rather than the 1000 of part 1.
+        part1: None,
+        part2: Some("25272"),
+    };
+
+    type Input = Vec<(i64, i64, i64)>;
+    type Answer1 = usize;
+    type Answer2 = Result<i64, SolveError>;
+
+    fn parse(s: &str) -> Result<Self::Input, ParseError> {
+        let mut seen = HashSet::new();
+        parse_all(s, parse)?
+            .into_iter()
+            .map(|(text, coord)| match seen.insert(coord) {
+                true => Ok(coord),
+                false => Err(ParseError::at(s, text, "duplicate junction box")),
+            })
+            .collect()
+    }
+
+    fn part1(items: &Self::Input) -> usize {
+        run(items, 1000)
+    }
+
+    fn part2(items: &Self::Input) -> Option<Result<i64, SolveError>> {
+        let mut circuits = UnionFind::new(items.len());
+        for (a, b) in closest_pairs(items) {
+            if circuits.merge(a, b) && circuits.component_count() == 1 {
+                return Some(Ok(items[a].0 * items[b].0));
+            }
+        }
+        // Only fewer than two boxes never need a connection to join up.
+        Some(Err(SolveError::new(
+            "expected at least 2 junction boxes to connect",
+        )))
+    }
+
+    /// `count` different boxes with coordinates of up to `width` digits.
+    fn generate(rng: &mut Rng, size: Size) -> Option<String> {
+        // Coordinates fit in an `i32`.
+        let digits = size.width_or(5).clamp(1, 9) as u32;
+        let max = 10u64.pow(digits) - 1;
+        let count = size
+            .count_or(1000)
+            .min((max as usize + 1).saturating_pow(3));
+        let mut seen = HashSet::new();
+        while seen.len() < count {
+            seen.insert((rng.range(0..=max), rng.range(0..=max), rng.range(0..=max)));
+        }
+        let mut boxes: Vec<_> = seen.into_iter().collect();
+        // Sets iterate in an order of their own, so the seed alone decides it.
+        boxes.sort();
+        rng.shuffle(&mut boxes);
+        let boxes = boxes.iter().map(|(x, y, z)| format!("{x},{y},{z}"));
+        Some(generate::lines(boxes))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// The sizes of the circuits, found by walking the connections from each box.
+    fn circuits(boxes: &[(i64, i64, i64)], edges: &[(usize, usize)]) -> Vec<usize> {
+        let mut seen = HashSet::new();
+        let mut sizes = Vec::new();
+        for start in 0..boxes.len() {
+            let mut stack = vec![start];
+            let mut size = 0;
+            while let Some(i) = stack.pop() {
+                if seen.insert(i) {
+                    size += 1;
+                    stack.extend(edges.iter().filter(|e| e.0 == i).map(|e| e.1));
+                    stack.extend(edges.iter().filter(|e| e.1 == i).map(|e| e.0));
+                }
+            }
+            if size > 0 {
+                sizes.push(size);
+            }
+        }
+        sizes
+    }
+
+    /// Connects the closest pairs one at a time, recounting the circuits
+    /// from scratch after each.
+    fn naive(boxes: &[(i64, i64, i64)], n_connections: usize) -> (usize, i64) {
+        let mut pairs: Vec<_> = (0..boxes.len())
+            .flat_map(|i| (i + 1..boxes.len()).map(move |j| (i, j)))
+            .collect();
+        pairs.sort_by_key(|&(i, j)| distance(boxes[i], boxes[j]));
+        let mut edges = Vec::new();
+        let mut product = None;
+        let mut last = 0;
+        for (n, &(i, j)) in pairs.iter().enumerate() {
+            if n == n_connections {
+                product = Some(circuits(boxes, &edges));
+            }
+            let before = circuits(boxes, &edges).len();
+            edges.push((i, j));
+            if circuits(boxes, &edges).len() < before {
+                last = boxes[i].0 * boxes[j].0;
+            }
+        }
+        let mut sizes = product.unwrap_or_else(|| circuits(boxes, &edges));
+        sizes.sort();
+        (sizes.iter().rev().take(3).product(), last)
+    }
+
+    /// Boxes whose pairwise distances are all different, so that the order of
+    /// connections is unambiguous.
+    fn boxes() -> impl Strategy<Value = Vec<(i64, i64, i64)>> {
+        let coord = 0..1000i64;
+        prop::collection::vec((coord.clone(), coord.clone(), coord), 2..12).prop_filter(
+            "distances must be distinct",
+            |boxes| {
+                let distances: Vec<_> = boxes
+                    .iter()
+                    .tuple_combinations()
+                    .map(|(a, b)| distance(*a, *b))
+                    .collect();
+                distances.iter().all_unique() && !distances.contains(&0)
+            },
+        )
+    }
+
     #[test]
     fn test_sample() {
-        assert_eq!(run(SAMPLE, 10), 40);
+        assert_eq!(run(&Day8::parse(SAMPLE).unwrap(), 10), 40);
     }
 
     #[test]
     fn test_sample_2() {
-        assert_eq!(solve_2(SAMPLE), 25272);
+        assert_eq!(Day8::part2(&Day8::parse(SAMPLE).unwrap()), Some(Ok(25272)));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = Day8::parse("1,2,3\n4,5,6\n1,2,3").err().unwrap();
+        assert_eq!((error.line, error.column), (3, 1));
+        assert!(Day8::parse("1,2,3000000000").is_err());
+        assert!(matches!(Day8::part2(&vec![(1, 2, 3)]), Some(Err(_))));
+    }
+
+    proptest! {
+        #[test]
+        fn test_naive(boxes in boxes(), n_connections in 0..70usize) {
+            let (part1, part2) = naive(&boxes, n_connections);
+            prop_assert_eq!(run(&boxes, n_connections), part1);
+            prop_assert_eq!(Day8::part2(&boxes), Some(Ok(part2)));
+        }
     }
 }
@@ -1,43 +1,25 @@
-use std::{
-    collections::{HashMap, HashSet},
-    iter::zip,
-};
+use std::{collections::HashSet, iter::zip};
 
 use itertools::Itertools;
 use nom::{
-    bytes::complete::tag,
-    character::complete::{digit1, multispace0},
-    combinator::map_res,
-    multi::many0,
-    sequence::{preceded, separated_pair},
+    combinator::{consumed, map},
     IResult,
 };
 
-fn parse(s: &str) -> IResult<&str, Vec<(i64, i64)>> {
-    let num = || map_res(digit1, str::parse);
-    let coord = separated_pair(num(), tag(","), num());
-    many0(preceded(multispace0, coord))(s)
-}
-
-pub fn solve(s: &str) -> i64 {
-    let items = parse(s).unwrap().1;
-    items
-        .into_iter()
-        .tuple_combinations()
-        .map(|((x1, y1), (x2, y2))| (y1 - y2 + 1) * (x1 - x2 + 1))
-        .max()
-        .unwrap()
-}
+use crate::{
+    generate::{self, Rng, Size},
+    solution::{Sample, Solution},
+    utils::{
+        parse::{coords, lines_of, unsigned},
+        parse_all, Compressor, ParseError, Spanned,
+    },
+};
 
-fn compress(ps: &[i64]) -> (Vec<i64>, Vec<i64>) {
-    let mut fwd = HashMap::new();
-    let mut bwd = Vec::new();
-    for &p in ps.iter().unique().sorted() {
-        fwd.insert(p, fwd.len() as i64);
-        bwd.push(p);
-    }
-    let ps = ps.into_iter().map(|p| *fwd.get(p).unwrap()).collect();
-    (ps, bwd)
+fn parse(s: &str) -> IResult<&str, Vec<Spanned<'_, (i64, i64)>>> {
+    // Coordinates that fit in an `i32` keep areas within an `i64`.
+    let num = map(unsigned::<i32>, i64::from);
+    let coord = map(coords(num), |[x, y]| (x, y));
+    lines_of(consumed(coord))(s)
 }
 
 fn dir((x1, y1): (i64, i64), (x2, y2): (i64, i64)) -> (i64, i64) {
@@ -51,77 +33,275 @@ fn dir((x1, y1): (i64, i64), (x2, y2): (i64, i64)) -> (i64, i64) {
     }
 }
 
-pub fn solve_2(s: &str) -> i64 {
-    let items = parse(s).unwrap().1;
-    let xs: Vec<_> = items.iter().map(|(x, _)| *x).collect();
-    let ys: Vec<_> = items.iter().map(|(_, y)| *y).collect();
-    let (xs, x_key) = compress(&xs);
-    let (ys, y_key) = compress(&ys);
-    let mut points = Vec::new();
-    let mut normals = Vec::new();
-    let mut winding: i64 = 0;
-    for ((x0, y0), (mut x1, mut y1), (x2, y2)) in
-        zip(xs.clone(), ys.clone()).circular_tuple_windows()
-    {
-        let (dx, dy) = dir((x1, y1), (x2, y2));
-        let (dx0, dy0) = dir((x0, y0), (x1, y1));
-        winding += if dy0 * dx > dx0 * dy { -1 } else { 1 };
-        while (x1, y1) != (x2, y2) {
-            points.push((x1, y1));
-            normals.push((-dy, dx)); // Assuming clockwise
-            (x1, y1) = (x1 + dx, y1 + dy);
+const SAMPLE: &str = "
+        7,1
+        11,1
+        11,7
+        9,7
+        9,5
+        2,5
+        2,3
+        7,3";
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: u32 = 9;
+    const SAMPLE: Sample = Sample {
+        input: SAMPLE,
+        part1: Some("50"),
+        part2: Some("24"),
+    };
+
+    type Input = Vec<(i64, i64)>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(s: &str) -> Result<Self::Input, ParseError> {
+        let tiles = parse_all(s, parse)?;
+        if tiles.len() < 4 {
+            let message = "expected a loop of at least 4 tiles";
+            return Err(ParseError::at(s, s.trim_start(), message));
         }
+        // Edges run along rows or columns and turn at every tile.
+        let straight = |(x1, y1): (i64, i64), (x2, y2): (i64, i64)| (x1 == x2) != (y1 == y2);
+        for (&(_, a), &(b_text, b), &(c_text, c)) in tiles.iter().circular_tuple_windows() {
+            if !straight(b, c) {
+                let message = "expected a tile in the same row or column as the last";
+                return Err(ParseError::at(s, c_text, message));
+            }
+            if (a.0 == b.0) == (b.0 == c.0) {
+                return Err(ParseError::at(s, b_text, "expected the loop to turn here"));
+            }
+        }
+        Ok(tiles.into_iter().map(|(_, tile)| tile).collect())
+    }
+
+    fn part1(items: &Self::Input) -> i64 {
+        items
+            .iter()
+            .tuple_combinations()
+            .map(|((x1, y1), (x2, y2))| (1 + (y1 - y2).abs()) * (1 + (x1 - x2).abs()))
+            .max()
+            .unwrap()
     }
-    let winding = winding / winding.abs();
-    let mut stack: Vec<_> = zip(&points, normals)
-        .map(|((x, y), (nx, ny))| (x + winding * nx, y + winding * ny))
-        .collect();
-    let mut tiles: HashSet<_> = points.into_iter().collect();
-    while let Some((x, y)) = stack.pop() {
-        if tiles.insert((x, y)) {
-            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-                stack.push((x + dx, y + dy));
+
+    fn part2(items: &Self::Input) -> Option<i64> {
+        // Gaps keep the room between edges, so that the fill can get around them.
+        let [x_key, y_key] = Compressor::axes_with_gaps(items.iter().map(|&(x, y)| [x, y]));
+        let compress = |key: &Compressor<i64>, p| key.compress(p).unwrap() as i64;
+        let xs: Vec<_> = items.iter().map(|&(x, _)| compress(&x_key, x)).collect();
+        let ys: Vec<_> = items.iter().map(|&(_, y)| compress(&y_key, y)).collect();
+        let mut points = Vec::new();
+        let mut normals = Vec::new();
+        let mut winding: i64 = 0;
+        for ((x0, y0), (mut x1, mut y1), (x2, y2)) in
+            zip(xs.clone(), ys.clone()).circular_tuple_windows()
+        {
+            let (dx, dy) = dir((x1, y1), (x2, y2));
+            let (dx0, dy0) = dir((x0, y0), (x1, y1));
+            winding += if dy0 * dx > dx0 * dy { -1 } else { 1 };
+            while (x1, y1) != (x2, y2) {
+                points.push((x1, y1));
+                normals.push((-dy, dx)); // Assuming clockwise
+                (x1, y1) = (x1 + dx, y1 + dy);
+            }
+        }
+        let winding = winding.signum();
+        let mut stack: Vec<_> = zip(&points, normals)
+            .map(|((x, y), (nx, ny))| (x + winding * nx, y + winding * ny))
+            .collect();
+        let mut tiles: HashSet<_> = points.into_iter().collect();
+        // A loop that crosses itself may let the fill out, so it stops at the edges.
+        let inside = |x: i64, y: i64| {
+            (0..x_key.len() as i64).contains(&x) && (0..y_key.len() as i64).contains(&y)
+        };
+        while let Some((x, y)) = stack.pop() {
+            if inside(x, y) && tiles.insert((x, y)) {
+                for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    stack.push((x + dx, y + dy));
+                }
             }
         }
+        // sums[x][y] counts the tiles left of x and above y.
+        let mut sums = vec![vec![0; y_key.len() + 1]; x_key.len() + 1];
+        for x in 0..x_key.len() {
+            for y in 0..y_key.len() {
+                let tile = tiles.contains(&(x as i64, y as i64)) as i64;
+                sums[x + 1][y + 1] = tile + sums[x][y + 1] + sums[x + 1][y] - sums[x][y];
+            }
+        }
+        let area = zip(xs, ys)
+            .tuple_combinations()
+            .filter(|&((x1, y1), (x2, y2))| {
+                let (x1, x2) = (x1.min(x2) as usize, x1.max(x2) as usize + 1);
+                let (y1, y2) = (y1.min(y2) as usize, y1.max(y2) as usize + 1);
+                let count = sums[x2][y2] - sums[x1][y2] - sums[x2][y1] + sums[x1][y1];
+                count == ((x2 - x1) * (y2 - y1)) as i64
+            })
+            .map(|((x1, y1), (x2, y2))| {
+                let (x1, y1) = (x_key.decompress(x1 as usize), y_key.decompress(y1 as usize));
+                let (x2, y2) = (x_key.decompress(x2 as usize), y_key.decompress(y2 as usize));
+                (1 + (y1 - y2).abs()) * (1 + (x1 - x2).abs())
+            })
+            .max()
+            .unwrap();
+        Some(area)
+    }
+
+    /// A loop of about `count` tiles with coordinates of up to `width` digits.
+    ///
+    /// The loop runs along the tops of columns of varying height, then back
+    /// along their bottoms. Tops stay above the middle and bottoms below it,
+    /// so the two sides never meet, and neighbouring columns differ at both
+    /// ends, so the loop turns at every tile.
+    fn generate(rng: &mut Rng, size: Size) -> Option<String> {
+        // Coordinates fit in an `i32`, and leave room for two bottoms and
+        // two tops to tell apart.
+        let max = 10u64.pow(size.width_or(5).clamp(1, 9) as u32) - 1;
+        let mid = max / 2;
+        let columns = (size.count_or(500) / 4).clamp(1, max as usize);
+        let mut xs = HashSet::new();
+        while xs.len() < columns + 1 {
+            xs.insert(rng.range(0..=max));
+        }
+        let xs: Vec<_> = xs.into_iter().sorted().collect();
+        let mut ends: Vec<(u64, u64)> = Vec::new();
+        for _ in 0..columns {
+            let (bottom, top) = loop {
+                let end = (rng.range(0..=mid - 1), rng.range(mid + 1..=max));
+                match ends.last() {
+                    Some(last) if last.0 == end.0 || last.1 == end.1 => continue,
+                    _ => break end,
+                }
+            };
+            ends.push((bottom, top));
+        }
+        let mut tiles = Vec::new();
+        for (i, &(_, top)) in ends.iter().enumerate() {
+            tiles.extend([(xs[i], top), (xs[i + 1], top)]);
+        }
+        for (i, &(bottom, _)) in ends.iter().enumerate().rev() {
+            tiles.extend([(xs[i + 1], bottom), (xs[i], bottom)]);
+        }
+        if rng.chance(0.5) {
+            tiles.iter_mut().for_each(|(x, y)| (*x, *y) = (*y, *x));
+        }
+        if rng.chance(0.5) {
+            tiles.reverse();
+        }
+        let start = rng.index(tiles.len());
+        tiles.rotate_left(start);
+        Some(generate::lines(
+            tiles.iter().map(|(x, y)| format!("{x},{y}")),
+        ))
     }
-    zip(xs, ys)
-        .into_iter()
-        .tuple_combinations()
-        .filter(|&((x1, y1), (x2, y2))| {
-            (x1.min(x2)..=x1.max(x2))
-                .cartesian_product(y1.min(y2)..=y1.max(y2))
-                .all(|p| tiles.contains(&p))
-        })
-        .map(|((x1, y1), (x2, y2))| {
-            let (x1, y1) = (x_key[x1 as usize], y_key[y1 as usize]);
-            let (x2, y2) = (x_key[x2 as usize], y_key[y2 as usize]);
-            (1 + (y1 - y2).abs()) * (1 + (x1 - x2).abs())
-        })
-        .max()
-        .unwrap()
 }
 
 #[cfg(test)]
 mod test {
+    use proptest::prelude::*;
+
     use super::*;
 
-    const SAMPLE: &'static str = "
-        7,1
-        11,1
-        11,7
-        9,7
-        9,5
-        2,5
-        2,3
-        7,3";
+    fn area((x1, y1): (i64, i64), (x2, y2): (i64, i64)) -> i64 {
+        (1 + (x1 - x2).abs()) * (1 + (y1 - y2).abs())
+    }
+
+    /// Whether `(x, y)` is on the loop or enclosed by it, by counting the
+    /// vertical edges crossed on the way out to the left.
+    fn inside(tiles: &[(i64, i64)], (x, y): (i64, i64)) -> bool {
+        let edges = || tiles.iter().zip(tiles.iter().cycle().skip(1));
+        let on_edge = edges().any(|(&(x1, y1), &(x2, y2))| {
+            (x1.min(x2)..=x1.max(x2)).contains(&x) && (y1.min(y2)..=y1.max(y2)).contains(&y)
+        });
+        let crossings = edges()
+            .filter(|&(&(x1, y1), &(x2, y2))| {
+                x1 == x2 && x1 < x && y1.min(y2) <= y && y < y1.max(y2)
+            })
+            .count();
+        on_edge || crossings % 2 == 1
+    }
+
+    /// Checks every tile of every rectangle.
+    fn naive(tiles: &[(i64, i64)]) -> (i64, i64) {
+        let pairs = || tiles.iter().tuple_combinations();
+        let part1 = pairs().map(|(&a, &b)| area(a, b)).max().unwrap();
+        let part2 = pairs()
+            .filter(|&(&(x1, y1), &(x2, y2))| {
+                (x1.min(x2)..=x1.max(x2))
+                    .cartesian_product(y1.min(y2)..=y1.max(y2))
+                    .all(|p| inside(tiles, p))
+            })
+            .map(|(&a, &b)| area(a, b))
+            .max()
+            .unwrap();
+        (part1, part2)
+    }
+
+    /// A loop around a skyline: a flat bottom and columns of varying height,
+    /// mirrored, transposed and started at any corner.
+    fn skyline() -> impl Strategy<Value = Vec<(i64, i64)>> {
+        let column = (1..4i64, 1..8i64);
+        (
+            prop::collection::vec(column, 1..6),
+            any::<(bool, bool, bool)>(),
+            any::<prop::sample::Index>(),
+        )
+            .prop_filter_map(
+                "neighbouring columns need different heights",
+                |(columns, (mirror, transpose, reverse), start)| {
+                    if columns.windows(2).any(|w| w[0].1 == w[1].1) {
+                        return None;
+                    }
+                    let mut tiles = vec![(0, 0)];
+                    let mut x = 0;
+                    for &(width, height) in &columns {
+                        tiles.push((x, height));
+                        x += width;
+                        tiles.push((x, height));
+                    }
+                    tiles.push((x, 0));
+                    let mut tiles: Vec<_> = tiles
+                        .into_iter()
+                        .map(|(x, y)| if mirror { (20 - x, y) } else { (x, y) })
+                        .map(|(x, y)| if transpose { (y, x) } else { (x, y) })
+                        .collect();
+                    if reverse {
+                        tiles.reverse();
+                    }
+                    let start = start.index(tiles.len());
+                    tiles.rotate_left(start);
+                    Some(tiles)
+                },
+            )
+    }
 
     #[test]
     fn test_sample() {
-        assert_eq!(solve(SAMPLE), 50);
+        assert_eq!(Day9::part1(&Day9::parse(SAMPLE).unwrap()), 50);
     }
 
     #[test]
     fn test_sample_2() {
-        assert_eq!(solve_2(SAMPLE), 24);
+        assert_eq!(Day9::part2(&Day9::parse(SAMPLE).unwrap()), Some(24));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Day9::parse("").is_err());
+        let error = Day9::parse("0,0\n0,2\n2,3\n2,0").err().unwrap();
+        assert_eq!((error.line, error.column), (3, 1));
+        let error = Day9::parse("0,0\n0,1\n0,2\n2,2\n2,0").err().unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+    }
+
+    proptest! {
+        #[test]
+        fn test_naive(tiles in skyline()) {
+            let (part1, part2) = naive(&tiles);
+            prop_assert_eq!(Day9::part1(&tiles), part1);
+            prop_assert_eq!(Day9::part2(&tiles), Some(part2));
+        }
     }
 }
@@ -0,0 +1,194 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// The least time between two requests to the server.
+pub const MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Downloads puzzle inputs into a cache directory, at most one request per
+/// `min_interval` even across separate runs.
+pub struct Fetcher {
+    pub base_url: String,
+    pub year: u32,
+    /// The session cookie, or `None` to read it with `session_token` once a
+    /// download is needed.
+    pub session: Option<String>,
+    pub cache_dir: PathBuf,
+    pub min_interval: Duration,
+}
+
+/// Reads the session cookie from `AOC_SESSION`, or else from the config file
+/// `~/.config/adventofcode/session`.
+pub fn session_token() -> io::Result<String> {
+    if let Ok(token) = env::var("AOC_SESSION") {
+        return Ok(token.trim().to_string());
+    }
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    let path = config.join("adventofcode").join("session");
+    match fs::read_to_string(&path) {
+        Ok(token) => Ok(token.trim().to_string()),
+        Err(e) => Err(io::Error::new(
+            e.kind(),
+            format!("set AOC_SESSION or write the token to {}", path.display()),
+        )),
+    }
+}
+
+fn now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+impl Fetcher {
+    pub fn input_path(&self, day: u32) -> PathBuf {
+        self.cache_dir.join(format!("{day}.txt"))
+    }
+
+    fn stamp_path(&self) -> PathBuf {
+        self.cache_dir.join(".last-fetch")
+    }
+
+    /// Sleeps until `min_interval` has passed since the last recorded request.
+    fn wait_for_turn(&self) {
+        let last = fs::read_to_string(self.stamp_path())
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or_default();
+        if let Some(wait) = (last + self.min_interval).checked_sub(now()) {
+            thread::sleep(wait.min(self.min_interval));
+        }
+    }
+
+    /// Returns the cached input for `day`, downloading it first if needed.
+    ///
+    /// The flag is whether a download happened. An empty file, as left by
+    /// `new-day`, does not count as cached.
+    pub fn fetch(&self, day: u32) -> io::Result<(PathBuf, bool)> {
+        let path = self.input_path(day);
+        if fs::metadata(&path).is_ok_and(|m| m.len() > 0) {
+            return Ok((path, false));
+        }
+        let session = match &self.session {
+            Some(session) => session.clone(),
+            None => session_token()?,
+        };
+        self.wait_for_turn();
+        let url = format!(
+            "{}/{}/day/{day}/input",
+            self.base_url.trim_end_matches('/'),
+            self.year
+        );
+        let response = ureq::get(&url)
+            .set("Cookie", &format!("session={session}"))
+            .set("User-Agent", "github.com/rfsmi/adventofcode-2025")
+            .call();
+        fs::write(self.stamp_path(), now().as_millis().to_string())?;
+        let body = match response {
+            Ok(response) => response.into_string()?,
+            Err(ureq::Error::Status(404, _)) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{url}: input for day {day} is not available"),
+                ))
+            }
+            Err(e) => return Err(io::Error::other(format!("{url}: {e}"))),
+        };
+        // Write through a temporary file so that a failed write is never cached.
+        let partial = path.with_extension("txt.part");
+        fs::write(&partial, body)?;
+        fs::rename(&partial, &path)?;
+        Ok((path, true))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::mpsc,
+    };
+
+    use super::*;
+
+    /// Serves `count` requests with `body`, reporting each request line.
+    fn serve(body: &'static str, count: usize) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.unwrap();
+                let mut lines = BufReader::new(stream.try_clone().unwrap()).lines();
+                let request = lines.next().unwrap().unwrap();
+                let cookie = lines
+                    .map_while(Result::ok)
+                    .take_while(|l| !l.is_empty())
+                    .find(|l| l.to_lowercase().starts_with("cookie:"))
+                    .unwrap_or_default();
+                tx.send(format!("{request} | {cookie}")).unwrap();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, rx)
+    }
+
+    fn fetcher(base_url: String, name: &str) -> Fetcher {
+        let cache_dir = env::temp_dir().join(format!("aoc-fetch-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&cache_dir);
+        fs::create_dir_all(&cache_dir).unwrap();
+        Fetcher {
+            base_url,
+            year: 2025,
+            session: Some("secret".into()),
+            cache_dir,
+            min_interval: Duration::from_millis(200),
+        }
+    }
+
+    #[test]
+    fn test_fetch_caches() {
+        let (url, requests) = serve("1,2,3\n", 1);
+        let fetcher = fetcher(url, "caches");
+        fs::write(fetcher.input_path(7), "").unwrap();
+        let (path, downloaded) = fetcher.fetch(7).unwrap();
+        assert!(downloaded);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1,2,3\n");
+        assert_eq!(
+            requests.recv().unwrap().to_lowercase(),
+            "get /2025/day/7/input http/1.1 | cookie: session=secret"
+        );
+        // A cached input needs no session token.
+        let fetcher = Fetcher {
+            session: None,
+            ..fetcher
+        };
+        assert_eq!(fetcher.fetch(7).unwrap(), (path, false));
+        fs::remove_dir_all(&fetcher.cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_fetch_rate_limited() {
+        let (url, _requests) = serve("x\n", 2);
+        let fetcher = fetcher(url, "rate");
+        let start = std::time::Instant::now();
+        fetcher.fetch(1).unwrap();
+        fetcher.fetch(2).unwrap();
+        assert!(start.elapsed() >= fetcher.min_interval);
+        fs::remove_dir_all(&fetcher.cache_dir).unwrap();
+    }
+}
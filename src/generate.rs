@@ -0,0 +1,118 @@
+use std::ops::RangeInclusive;
+
+/// A small seeded generator (SplitMix64), so that the same seed always gives
+/// the same input without pulling in a crate for it.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `range`, which must not be empty.
+    pub fn range(&mut self, range: RangeInclusive<u64>) -> u64 {
+        let (lo, hi) = range.into_inner();
+        assert!(lo <= hi, "empty range {lo}..={hi}");
+        match (hi - lo).checked_add(1) {
+            // Multiplying instead of taking a remainder keeps the bias negligible.
+            Some(n) => lo + ((self.next_u64() as u128 * n as u128) >> 64) as u64,
+            None => self.next_u64(),
+        }
+    }
+
+    /// An index below `n`, which must not be zero.
+    pub fn index(&mut self, n: usize) -> usize {
+        self.range(0..=n as u64 - 1) as usize
+    }
+
+    /// A number of up to `digits` digits, as likely to be short as long.
+    pub fn number(&mut self, digits: u32) -> u64 {
+        let digits = self.range(1..=u64::from(digits)) as u32;
+        self.range(10u64.pow(digits - 1)..=10u64.pow(digits) - 1)
+    }
+
+    /// `true` with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+}
+
+/// How large an input to generate. Each day reads `count` and `width` in its
+/// own terms, and falls back to the size of its real input where they are
+/// `None`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Size {
+    /// How many items: lines, ranges, boxes, corners or machines.
+    pub count: Option<usize>,
+    /// How large each item is: digits, columns or lights.
+    pub width: Option<usize>,
+}
+
+impl Size {
+    pub fn count_or(&self, default: usize) -> usize {
+        self.count.unwrap_or(default)
+    }
+
+    pub fn width_or(&self, default: usize) -> usize {
+        self.width.unwrap_or(default)
+    }
+}
+
+/// Joins lines into an input, ending with a newline as real inputs do.
+pub fn lines(lines: impl IntoIterator<Item = String>) -> String {
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_seeded() {
+        let draw = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..8).map(|_| rng.next_u64()).collect::<Vec<_>>()
+        };
+        assert_eq!(draw(7), draw(7));
+        assert_ne!(draw(7), draw(8));
+    }
+
+    #[test]
+    fn test_range() {
+        let mut rng = Rng::new(0);
+        let draws: Vec<_> = (0..1000).map(|_| rng.range(3..=5)).collect();
+        assert!(draws.iter().all(|n| (3..=5).contains(n)));
+        assert!((3..=5).all(|n| draws.contains(&n)));
+        assert_eq!(rng.range(9..=9), 9);
+        assert!((0..100).all(|_| (1..=999).contains(&rng.number(3))));
+        rng.range(0..=u64::MAX);
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let mut items: Vec<_> = (0..50).collect();
+        Rng::new(1).shuffle(&mut items);
+        assert_ne!(items, (0..50).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..50).collect::<Vec<_>>());
+    }
+}
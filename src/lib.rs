@@ -0,0 +1,21 @@
+pub mod alloc;
+pub mod answer;
+pub mod answers;
+pub mod bench;
+pub mod day1;
+pub mod day10;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod fetch;
+pub mod generate;
+pub mod report;
+pub mod scaffold;
+pub mod solution;
+pub mod utils;
+pub mod watch;
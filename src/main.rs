@@ -1,27 +1,527 @@
-use clap::Parser;
+use std::{
+    any::Any,
+    fmt,
+    io::{self, ErrorKind},
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
-mod utils;
+use adventofcode::{
+    alloc, answers, bench, fetch, generate, report, scaffold,
+    solution::{self, Day, DAYS},
+    utils::{self, log},
+    watch,
+};
+use clap::{Parser, Subcommand};
+
+/// What to run: `dayN` or `dayN-2` for one part, `latest` or `all`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Task {
+    Part { day: u32, part: u32 },
+    Latest,
+    All,
+}
+
+impl FromStr for Task {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let task = match s {
+            "latest" => Task::Latest,
+            "all" => Task::All,
+            _ => {
+                let day = s
+                    .strip_prefix("day")
+                    .ok_or("expected `dayN`, `latest` or `all`")?;
+                let (day, part) = day.split_once('-').unwrap_or((day, "1"));
+                match (day.parse(), part.parse()) {
+                    (Ok(day), Ok(part @ (1 | 2))) => Task::Part { day, part },
+                    _ => return Err("expected `dayN` or `dayN-2`".into()),
+                }
+            }
+        };
+        Ok(task)
+    }
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Task::Part { day, part: 1 } => write!(f, "day{day}"),
+            Task::Part { day, part } => write!(f, "day{day}-{part}"),
+            Task::Latest => write!(f, "latest"),
+            Task::All => write!(f, "all"),
+        }
+    }
+}
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    #[arg(value_enum, default_value_t=Task::Latest)]
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// `dayN` or `dayN-2` for one part, `latest` or `all`
+    ///
+    /// Exits with status 1 if any answer fails its check or cannot be computed.
+    #[arg(default_value_t = Task::Latest)]
     task: Task,
+
+    /// Show what the solvers report; repeat for more detail
+    #[arg(long, short, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Show only results and errors
+    #[arg(long, short, conflicts_with = "verbose", global = true)]
+    quiet: bool,
+
+    /// Read the puzzle input from this file instead of `inputs/`, or `-` for stdin
+    #[arg(long)]
+    input: Option<PathBuf>,
+
+    /// Run against the example from the puzzle text, checking its known answers
+    #[arg(long, conflicts_with_all = ["input", "record"])]
+    sample: bool,
+
+    /// How to print the results
+    #[arg(long, value_enum, default_value_t = report::Format::Text)]
+    format: report::Format,
+
+    /// The file of known answers that results are checked against [default: inputs/answers.toml]
+    #[arg(long, value_name = "PATH")]
+    answers: Option<PathBuf>,
+
+    /// Write answers that are not in the answers file yet into it
+    #[arg(long, conflicts_with = "input")]
+    record: bool,
+
+    /// Re-run whenever an input file changes, comparing against the last run
+    ///
+    /// Watches the `--input` file, or else `inputs/N.txt` for each selected day.
+    /// Samples are compiled in, so `--sample` cannot be watched.
+    #[arg(long, conflicts_with_all = ["bench", "sample"])]
+    watch: bool,
+
+    /// Run up to this many solvers at once
+    #[arg(long, short, value_name = "N", default_value_t = 1)]
+    jobs: usize,
+
+    /// Benchmark the selected solvers over this many timed runs
+    #[arg(long, value_name = "N")]
+    bench: Option<usize>,
+
+    /// Save the benchmark results to this file
+    #[arg(long, value_name = "PATH", requires = "bench")]
+    save_baseline: Option<PathBuf>,
+
+    /// Compare the benchmark results against a previously saved baseline
+    #[arg(long, value_name = "PATH", requires = "bench")]
+    baseline: Option<PathBuf>,
+
+    /// Flag medians that are this many percent slower than the baseline
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        default_value_t = 10.0,
+        requires = "baseline"
+    )]
+    threshold: f64,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate and register `src/dayN.rs`, and an empty `inputs/N.txt` if not fetched yet
+    NewDay { day: u32 },
+
+    /// Download `inputs/N.txt` unless it is already there
+    ///
+    /// The session token comes from `AOC_SESSION` or `~/.config/adventofcode/session`.
+    Fetch {
+        day: u32,
+
+        /// The server to download from, also settable with `AOC_BASE_URL`
+        #[arg(long, value_name = "URL")]
+        base_url: Option<String>,
+
+        #[arg(long, default_value_t = 2025)]
+        year: u32,
+    },
+
+    /// Print a random input for a day, for stress tests and benchmarks
+    ///
+    /// Each day reads the sizes in its own terms and defaults to the size of a real input.
+    Generate {
+        day: u32,
+
+        /// How many items: lines, ranges, boxes, corners or machines
+        #[arg(long, value_name = "N")]
+        count: Option<usize>,
+
+        /// How large each item is: digits, columns or lights
+        #[arg(long, value_name = "N")]
+        width: Option<usize>,
+
+        /// Start from this seed rather than a random one, to get the same input again
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+}
+
+fn default_answers_path() -> PathBuf {
+    [env!("CARGO_MANIFEST_DIR"), "inputs", "answers.toml"]
+        .iter()
+        .collect()
+}
+
+/// The days a task covers, each with the parts to run in order.
+///
+/// `latest` only runs the first of its parts that exists.
+fn selection(task: Task) -> io::Result<Vec<(&'static Day, &'static [u32])>> {
+    let selection = match task {
+        Task::Part { day, part } => {
+            let day = solution::find(day).ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("no solution for day {day}"),
+                )
+            })?;
+            vec![(day, if part == 1 { &[1][..] } else { &[2] })]
+        }
+        Task::Latest => vec![(DAYS.last().unwrap(), &[2, 1][..])],
+        Task::All => DAYS.iter().map(|day| (day, &[1, 2][..])).collect(),
+    };
+    Ok(selection)
+}
+
+fn run(args: Args) -> io::Result<()> {
+    if args.task == Task::All && args.input.is_some() {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "--input cannot be used with `all`",
+        ));
+    }
+    let selection = selection(args.task)?;
+    if let Some(runs) = args.bench {
+        return run_bench(&args, &selection, runs);
+    }
+    let answers_path = args.answers.clone().unwrap_or_else(default_answers_path);
+    let mut answers = answers::Answers::load(&answers_path)?;
+    if !args.watch {
+        let (records, total) = evaluate(&args, &selection, &answers)?;
+        report::print(args.format, &records, total);
+        record_answers(&args, &records, &mut answers, &answers_path)?;
+        return match records.iter().filter(|r| r.status.is_failure()).count() {
+            0 => Ok(()),
+            n => Err(io::Error::other(format!(
+                "{n} answer(s) failed or could not be computed"
+            ))),
+        };
+    }
+    let paths = match &args.input {
+        Some(path) if path == Path::new("-") => {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "--watch cannot read the input from stdin",
+            ))
+        }
+        Some(path) => vec![path.clone()],
+        None => selection
+            .iter()
+            .map(|(day, _)| utils::input_path(day.day))
+            .collect(),
+    };
+    let mut watcher = watch::Watcher::new(paths, watch::POLL_INTERVAL);
+    let mut previous: Option<Vec<report::Record>> = None;
+    loop {
+        // A file may be caught half-written, so errors wait for the next change.
+        match evaluate(&args, &selection, &answers) {
+            Ok((records, total)) => {
+                match &previous {
+                    Some(previous) => report::print_changes(args.format, previous, &records, total),
+                    None => report::print(args.format, &records, total),
+                }
+                record_answers(&args, &records, &mut answers, &answers_path)?;
+                previous = Some(records);
+            }
+            Err(e) => eprintln!("error: {e}"),
+        }
+        log::info(format_args!("Watching for changes..."));
+        for path in watcher.wait() {
+            log::info(format_args!("Changed: {}", path.display()));
+        }
+    }
+}
+
+fn load_input(args: &Args, day: &Day) -> io::Result<String> {
+    match args.sample {
+        true => Ok(day.sample.input.to_string()),
+        false => utils::load_input(args.input.as_deref(), day.day, day.embedded),
+    }
+}
+
+/// Runs the selected solvers once, checking their answers.
+fn evaluate(
+    args: &Args,
+    selection: &[(&'static Day, &'static [u32])],
+    answers: &answers::Answers,
+) -> io::Result<(Vec<report::Record>, Duration)> {
+    let inputs = selection
+        .iter()
+        .map(|(day, _)| load_input(args, day))
+        .collect::<io::Result<Vec<_>>>()?;
+    let start = Instant::now();
+    let work: Vec<_> = selection.iter().zip(&inputs).collect();
+    let first_only = args.task == Task::Latest;
+    let mut records: Vec<_> = utils::parallel_map(args.jobs, &work, |&(&(day, parts), input)| {
+        run_day(day, parts, first_only, input)
+    })
+    .into_iter()
+    .flatten()
+    .collect();
+    let total = start.elapsed();
+    if let (Task::Part { day, part }, []) = (args.task, &records[..]) {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("day {day} has no part {part}"),
+        ));
+    }
+    // Stored answers belong to the real inputs, so a custom input stays unknown.
+    for record in &mut records {
+        if let (report::Status::Unknown, Some(answer)) = (&record.status, &record.answer) {
+            let verdict = match (args.sample, &args.input) {
+                (true, _) => solution::find(record.day)
+                    .unwrap()
+                    .sample
+                    .check(record.part, answer),
+                (false, None) => answers.check(record.day, record.part, answer),
+                (false, Some(_)) => continue,
+            };
+            record.status = verdict.into();
+        }
+    }
+    Ok((records, total))
+}
+
+/// Writes answers not yet in the answers file into it, with `--record`.
+fn record_answers(
+    args: &Args,
+    records: &[report::Record],
+    answers: &mut answers::Answers,
+    path: &Path,
+) -> io::Result<()> {
+    if args.record {
+        let mut changed = false;
+        for record in records {
+            if let (report::Status::Unknown, Some(answer)) = (&record.status, &record.answer) {
+                answers.insert(record.day, record.part, answer.clone());
+                changed = true;
+            }
+        }
+        if changed {
+            answers.save(path)?;
+        }
+    }
+    Ok(())
 }
 
-utils::make_runner!(
-    1+,
-    2+,
-    3+,
-    4+,
-    5+,
-    6+,
-    7+,
-    8+,
-    9+,
-    10+,
-);
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match (
+        payload.downcast_ref::<&str>(),
+        payload.downcast_ref::<String>(),
+    ) {
+        (Some(s), _) => s.to_string(),
+        (_, Some(s)) => s.clone(),
+        _ => "panicked".to_string(),
+    }
+}
+
+/// Parses the input of a day once and solves each of `parts` from it,
+/// turning a parse error, a solve error or a panic into an error record.
+///
+/// Parts the day does not have are skipped. With `first_only`, only the
+/// first part that exists is solved.
+fn run_day(day: &Day, parts: &[u32], first_only: bool, input: &str) -> Vec<report::Record> {
+    let start = Instant::now();
+    let parsed = panic::catch_unwind(AssertUnwindSafe(|| day.parse(input)));
+    let parse_time = start.elapsed();
+    let record = |part, answer, solve_time, memory, status| report::Record {
+        day: day.day,
+        part,
+        answer,
+        parse_time: Some(parse_time),
+        solve_time,
+        memory,
+        status,
+    };
+    let parsed = match parsed {
+        Ok(Ok(parsed)) => Ok(parsed),
+        Ok(Err(e)) => Err(format!("parse error: {e}")),
+        Err(payload) => Err(format!("parse error: {}", panic_message(payload))),
+    };
+    let parsed = match parsed {
+        Ok(parsed) => parsed,
+        // Every requested part fails with the parse, so each gets a record.
+        Err(e) => {
+            let parts = if first_only { &parts[..1] } else { parts };
+            return parts
+                .iter()
+                .map(|&part| {
+                    let status = report::Status::Error(e.clone());
+                    record(part, None, Duration::ZERO, None, status)
+                })
+                .collect();
+        }
+    };
+    let mut records = Vec::new();
+    for &part in parts {
+        let start = Instant::now();
+        let context = format!("day {} part {part}", day.day);
+        let (result, memory) = log::with_context(context, || {
+            alloc::measure(|| panic::catch_unwind(AssertUnwindSafe(|| day.solve(&parsed, part))))
+        });
+        let solve_time = start.elapsed();
+        let (answer, status) = match result {
+            Ok(Ok(None)) => continue,
+            Ok(Ok(Some(answer))) => (Some(answer), report::Status::Unknown),
+            Ok(Err(e)) => (None, report::Status::Error(e.to_string())),
+            Err(payload) => (None, report::Status::Error(panic_message(payload))),
+        };
+        records.push(record(part, answer, solve_time, memory, status));
+        if first_only {
+            break;
+        }
+    }
+    records
+}
+
+fn run_bench(args: &Args, selection: &[(&Day, &[u32])], runs: usize) -> io::Result<()> {
+    let mut results = bench::Baseline::default();
+    for &(day, parts) in selection {
+        let input = load_input(args, day)?;
+        for &part in parts {
+            match day.run(&input, part) {
+                Ok(Some(_)) => {}
+                Ok(None) => continue,
+                Err(e) => return Err(io::Error::new(ErrorKind::InvalidData, e)),
+            }
+            let stats = bench::measure(runs, || {
+                let _ = std::hint::black_box(day.run(&input, part));
+            });
+            results.0.insert((day.day, part), stats);
+            if args.task == Task::Latest {
+                break;
+            }
+        }
+    }
+    let baseline = match &args.baseline {
+        Some(path) => Some(bench::Baseline::load(path)?),
+        None => None,
+    };
+    let regressions = bench::report(&results, baseline.as_ref(), args.threshold);
+    if let Some(path) = &args.save_baseline {
+        results.save(path)?;
+    }
+    match regressions {
+        0 => Ok(()),
+        n => Err(io::Error::other(format!(
+            "{n} solver(s) regressed by more than {}%",
+            args.threshold
+        ))),
+    }
+}
+
+fn new_day(day: u32) -> io::Result<()> {
+    for path in scaffold::new_day(Path::new(env!("CARGO_MANIFEST_DIR")), day)? {
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+fn fetch(day: u32, base_url: Option<String>, year: u32) -> io::Result<()> {
+    let base_url = base_url
+        .or_else(|| std::env::var("AOC_BASE_URL").ok())
+        .unwrap_or_else(|| fetch::DEFAULT_BASE_URL.to_string());
+    let fetcher = fetch::Fetcher {
+        base_url,
+        year,
+        session: None,
+        cache_dir: Path::new(env!("CARGO_MANIFEST_DIR")).join("inputs"),
+        min_interval: fetch::MIN_INTERVAL,
+    };
+    match fetcher.fetch(day)? {
+        (path, true) => println!("Downloaded {}", path.display()),
+        (path, false) => println!("Already have {}", path.display()),
+    }
+    Ok(())
+}
+
+fn generate(day: u32, size: generate::Size, seed: Option<u64>) -> io::Result<()> {
+    let unknown = |what| io::Error::new(ErrorKind::InvalidInput, format!("{what} for day {day}"));
+    let day = solution::find(day).ok_or_else(|| unknown("no solution"))?;
+    let seed = seed.unwrap_or_else(|| {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH);
+        now.map_or(0, |d| d.as_nanos() as u64)
+    });
+    log::info(format_args!("Seed {seed}"));
+    let input = day
+        .generate(size, seed)
+        .ok_or_else(|| unknown("no generator"))?;
+    print!("{input}");
+    Ok(())
+}
 
 fn main() {
-    run(Args::parse());
+    let args = Args::parse();
+    log::set_verbosity(match (args.quiet, args.verbose) {
+        (true, _) => log::Verbosity::Quiet,
+        (false, 0) => log::Verbosity::Normal,
+        (false, 1) => log::Verbosity::Verbose,
+        (false, _) => log::Verbosity::Trace,
+    });
+    let result = match args.command {
+        Some(Command::NewDay { day }) => new_day(day),
+        Some(Command::Fetch {
+            day,
+            base_url,
+            year,
+        }) => fetch(day, base_url, year),
+        Some(Command::Generate {
+            day,
+            count,
+            width,
+            seed,
+        }) => generate(day, generate::Size { count, width }, seed),
+        None => run(args),
+    };
+    if let Err(e) = result {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_global_flags() {
+        for args in [["-v", "fetch", "3"], ["fetch", "3", "-v"]] {
+            let args = Args::try_parse_from(["adventofcode"].into_iter().chain(args)).unwrap();
+            assert_eq!(args.verbose, 1);
+            assert!(matches!(args.command, Some(Command::Fetch { day: 3, .. })));
+        }
+        let args = Args::try_parse_from(["adventofcode", "-q", "generate", "1"]).unwrap();
+        assert!(args.quiet);
+        assert!(matches!(
+            args.command,
+            Some(Command::Generate { day: 1, .. })
+        ));
+        let args = Args::try_parse_from(["adventofcode", "-v", "day3-2"]).unwrap();
+        assert_eq!(
+            (args.verbose, args.task),
+            (1, Task::Part { day: 3, part: 2 })
+        );
+    }
 }
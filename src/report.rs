@@ -0,0 +1,445 @@
+use std::time::Duration;
+
+use crate::{
+    alloc::{self, Usage},
+    answer::Answer,
+    answers::Verdict,
+};
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail { expected: Answer },
+    Unknown,
+    Error(String),
+}
+
+impl Status {
+    /// Whether this should fail a regression check: a wrong answer or an error.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Status::Fail { .. } | Status::Error(_))
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Status::Pass => "pass",
+            Status::Fail { .. } => "fail",
+            Status::Unknown => "unknown",
+            Status::Error(_) => "error",
+        }
+    }
+
+    fn expected(&self) -> Option<&Answer> {
+        match self {
+            Status::Fail { expected } => Some(expected),
+            _ => None,
+        }
+    }
+
+    fn error(&self) -> Option<&str> {
+        match self {
+            Status::Error(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// A multi-line expected answer does not fit here and is shown below the result.
+    fn label(&self) -> String {
+        match self {
+            Status::Fail { expected } if expected.is_multiline() => {
+                "FAIL (expected below)".to_string()
+            }
+            Status::Fail { expected } => format!("FAIL (expected {expected})"),
+            status => status.name().to_uppercase(),
+        }
+    }
+}
+
+impl From<Verdict> for Status {
+    fn from(verdict: Verdict) -> Self {
+        match verdict {
+            Verdict::Pass => Status::Pass,
+            Verdict::Fail { expected } => Status::Fail { expected },
+            Verdict::Unknown => Status::Unknown,
+        }
+    }
+}
+
+/// The outcome of running one part of one day.
+#[derive(Clone, Debug)]
+pub struct Record {
+    pub day: u32,
+    pub part: u32,
+    pub answer: Option<Answer>,
+    /// Time spent parsing the input, which both parts of a day share.
+    pub parse_time: Option<Duration>,
+    /// Time spent solving the part from the parsed input.
+    pub solve_time: Duration,
+    /// Heap used while solving, with the `alloc-stats` feature.
+    pub memory: Option<Usage>,
+    pub status: Status,
+}
+
+/// Prints `records` in the requested format.
+///
+/// The text format keeps the one-line message for a single solver and uses a
+/// summary table with the `total` runtime for several.
+pub fn print(format: Format, records: &[Record], total: Duration) {
+    match format {
+        Format::Text if records.len() == 1 => print_line(&records[0]),
+        Format::Text => print_table(records, total),
+        Format::Json => println!("{}", to_json(records)),
+        Format::Csv => print!("{}", to_csv(records)),
+    }
+}
+
+/// Prints `records` after a re-run, showing each result next to the one
+/// in `previous` for the same part. Only the text format compares results.
+pub fn print_changes(format: Format, previous: &[Record], records: &[Record], total: Duration) {
+    match format {
+        Format::Text => print!("{}", changes(previous, records)),
+        _ => print(format, records, total),
+    }
+}
+
+fn summary(record: &Record) -> String {
+    format!(
+        "{} [{}] in {:.3}s",
+        text_answer(record),
+        record.status.label(),
+        record.solve_time.as_secs_f32()
+    )
+}
+
+fn changes(previous: &[Record], records: &[Record]) -> String {
+    let mut out = String::new();
+    for record in records {
+        let before = previous
+            .iter()
+            .find(|r| (r.day, r.part) == (record.day, record.part));
+        let was = match before {
+            Some(before) if before.answer == record.answer && before.status == record.status => {
+                format!("unchanged, was {:.3}s", before.solve_time.as_secs_f32())
+            }
+            Some(before) => format!("was {}", summary(before)),
+            None => "new".to_string(),
+        };
+        out += &format!(
+            "day {} part {}: {} ({was})\n",
+            record.day,
+            record.part,
+            summary(record)
+        );
+    }
+    out
+}
+
+/// The rows of the result in text form, with any multi-line expected
+/// answer after them.
+fn text_rows(record: &Record) -> Vec<String> {
+    let mut rows: Vec<_> = text_answer(record).lines().map(str::to_string).collect();
+    if let Some(expected) = record.status.expected().filter(|e| e.is_multiline()) {
+        rows.push("expected:".to_string());
+        rows.extend(expected.to_string().lines().map(str::to_string));
+    }
+    rows
+}
+
+fn print_line(record: &Record) {
+    let day = match record.part {
+        1 => record.day.to_string(),
+        part => format!("{} (part {part})", record.day),
+    };
+    let duration = record.solve_time.as_secs_f32();
+    let mut notes = Vec::new();
+    if let Some(parse_time) = record.parse_time {
+        notes.push(format!("parsed in {:.3} seconds", parse_time.as_secs_f32()));
+    }
+    if let Some(memory) = record.memory {
+        notes.push(format!(
+            "peak {} in {} allocations",
+            alloc::format_bytes(memory.peak_bytes),
+            memory.allocations
+        ));
+    }
+    let parsed = match notes.is_empty() {
+        true => String::new(),
+        false => format!(" ({})", notes.join(", ")),
+    };
+    match (&record.status, &record.answer) {
+        (Status::Error(e), _) => {
+            println!("Failed day {day} after {duration:.3} seconds{parsed}: {e}")
+        }
+        (status, Some(answer))
+            if answer.is_multiline() || status.expected().is_some_and(Answer::is_multiline) =>
+        {
+            let status = status.label();
+            println!("Computed result for day {day} in {duration:.3} seconds{parsed} [{status}]:");
+            for row in text_rows(record) {
+                println!("    {row}");
+            }
+        }
+        (status, answer) => {
+            let result = answer.as_ref().map(Answer::to_string).unwrap_or_default();
+            let status = status.label();
+            println!(
+                "Computed result for day {day} in {duration:.3} seconds{parsed}: {result} [{status}]"
+            )
+        }
+    }
+}
+
+fn text_answer(record: &Record) -> String {
+    match &record.status {
+        Status::Error(e) => format!("error: {e}"),
+        _ => record
+            .answer
+            .as_ref()
+            .map(Answer::to_string)
+            .unwrap_or_default(),
+    }
+}
+
+fn print_table(records: &[Record], total: Duration) {
+    let width = records
+        .iter()
+        .flat_map(text_rows)
+        .map(|row| row.chars().count())
+        .chain(["Answer".len()])
+        .max()
+        .unwrap();
+    let memory = records.iter().any(|r| r.memory.is_some());
+    let memory_header = if memory {
+        "Peak        Allocs      "
+    } else {
+        ""
+    };
+    println!(
+        "Day  Part  {:<width$}  Parse     Solve     {memory_header}Status",
+        "Answer"
+    );
+    for record in records {
+        let (day, part) = (record.day, record.part);
+        let parse_time = record
+            .parse_time
+            .map_or("-".into(), |t| format!("{:.3}s", t.as_secs_f32()));
+        let duration = format!("{:.3}s", record.solve_time.as_secs_f32());
+        let rows = text_rows(record);
+        let answer = rows.first().map_or("", String::as_str);
+        let status = record.status.label();
+        let usage = match (memory, record.memory) {
+            (false, _) => String::new(),
+            (true, None) => format!("{:<10}  {:<10}  ", "-", "-"),
+            (true, Some(usage)) => format!(
+                "{:<10}  {:<10}  ",
+                alloc::format_bytes(usage.peak_bytes),
+                usage.allocations
+            ),
+        };
+        println!(
+            "{day:>3}  {part:>4}  {answer:<width$}  {parse_time:<8}  {duration:<8}  {usage}{status}"
+        );
+        for row in rows.iter().skip(1) {
+            println!("           {row}");
+        }
+    }
+    println!("Total runtime: {:.3} seconds", total.as_secs_f32());
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            c if (c as u32) < 0x20 => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Integers become JSON numbers, text a string and a grid an array of rows.
+fn json_answer(answer: &Answer) -> String {
+    match answer {
+        Answer::Text(s) => json_string(s),
+        Answer::Grid(rows) => {
+            let rows: Vec<_> = rows.iter().map(|row| json_string(row)).collect();
+            format!("[{}]", rows.join(","))
+        }
+        n => n.to_string(),
+    }
+}
+
+fn micros(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1e6)
+}
+
+fn to_json(records: &[Record]) -> String {
+    let objects: Vec<_> = records
+        .iter()
+        .map(|r| {
+            let answer = r.answer.as_ref().map_or("null".into(), json_answer);
+            let parse_time = r.parse_time.map_or("null".into(), micros);
+            let (peak_bytes, allocations) = match r.memory {
+                Some(m) => (m.peak_bytes.to_string(), m.allocations.to_string()),
+                None => ("null".into(), "null".into()),
+            };
+            let expected = r.status.expected().map_or("null".into(), json_answer);
+            let error = r.status.error().map_or("null".into(), json_string);
+            format!(
+                "{{\"day\":{},\"part\":{},\"answer\":{answer},\"parse_time_us\":{parse_time},\
+                 \"solve_time_us\":{},\"peak_bytes\":{peak_bytes},\"allocations\":{allocations},\
+                 \"status\":\"{}\",\"expected\":{expected},\"error\":{error}}}",
+                r.day,
+                r.part,
+                micros(r.solve_time),
+                r.status.name(),
+            )
+        })
+        .collect();
+    format!("[{}]", objects.join(","))
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn to_csv(records: &[Record]) -> String {
+    let mut out = String::from(
+        "day,part,answer,parse_time_us,solve_time_us,peak_bytes,allocations,status,expected,error\n",
+    );
+    for r in records {
+        let expected = csv_field(
+            &r.status
+                .expected()
+                .map(Answer::to_string)
+                .unwrap_or_default(),
+        );
+        let error = csv_field(r.status.error().unwrap_or_default());
+        out += &format!(
+            "{},{},{},{},{},{},{},{},{expected},{error}\n",
+            r.day,
+            r.part,
+            csv_field(&r.answer.as_ref().map(Answer::to_string).unwrap_or_default()),
+            r.parse_time.map(micros).unwrap_or_default(),
+            micros(r.solve_time),
+            r.memory
+                .map(|m| m.peak_bytes.to_string())
+                .unwrap_or_default(),
+            r.memory
+                .map(|m| m.allocations.to_string())
+                .unwrap_or_default(),
+            r.status.name(),
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record {
+                day: 1,
+                part: 2,
+                answer: Some(6.into()),
+                parse_time: None,
+                solve_time: Duration::from_micros(1500),
+                memory: Some(Usage {
+                    peak_bytes: 2048,
+                    allocations: 3,
+                }),
+                status: Status::Fail { expected: 7.into() },
+            },
+            Record {
+                day: 3,
+                part: 1,
+                answer: None,
+                parse_time: Some(Duration::from_micros(2)),
+                solve_time: Duration::ZERO,
+                memory: None,
+                status: Status::Error("bad \"input\"\n".into()),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_changes() {
+        let previous = records();
+        let mut records = records();
+        records[0].answer = Some(7.into());
+        records[0].status = Status::Pass;
+        records[0].solve_time = Duration::from_millis(2);
+        records[1].part = 2;
+        assert_eq!(
+            changes(&previous, &records),
+            "day 1 part 2: 7 [PASS] in 0.002s (was 6 [FAIL (expected 7)] in 0.002s)\n\
+             day 3 part 2: error: bad \"input\"\n [ERROR] in 0.000s (new)\n"
+        );
+        assert_eq!(
+            changes(&previous, &previous[..1]),
+            "day 1 part 2: 6 [FAIL (expected 7)] in 0.002s (unchanged, was 0.002s)\n"
+        );
+    }
+
+    #[test]
+    fn test_grid() {
+        let mut record = records().remove(0);
+        record.answer = Some(Answer::grid(["#.", ".#"]));
+        record.status = Status::Fail {
+            expected: Answer::grid(["##", ".#"]),
+        };
+        assert_eq!(record.status.label(), "FAIL (expected below)");
+        assert_eq!(text_rows(&record), ["#.", ".#", "expected:", "##", ".#"]);
+        assert_eq!(
+            to_json(&[record]),
+            "[{\"day\":1,\"part\":2,\"answer\":[\"#.\",\".#\"],\"parse_time_us\":null,\
+             \"solve_time_us\":1500.000,\"peak_bytes\":2048,\"allocations\":3,\
+             \"status\":\"fail\",\"expected\":[\"##\",\".#\"],\
+             \"error\":null}]"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            to_json(&records()),
+            "[{\"day\":1,\"part\":2,\"answer\":6,\"parse_time_us\":null,\
+             \"solve_time_us\":1500.000,\"peak_bytes\":2048,\"allocations\":3,\
+             \"status\":\"fail\",\"expected\":7,\"error\":null},\
+             {\"day\":3,\"part\":1,\"answer\":null,\"parse_time_us\":2.000,\
+             \"solve_time_us\":0.000,\"peak_bytes\":null,\"allocations\":null,\
+             \"status\":\"error\",\"expected\":null,\
+             \"error\":\"bad \\\"input\\\"\\n\"}]"
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(
+            to_csv(&records()),
+            "day,part,answer,parse_time_us,solve_time_us,peak_bytes,allocations,status,expected,error\n\
+             1,2,6,,1500.000,2048,3,fail,7,\n\
+             3,1,,2.000,0.000,,,error,,\"bad \"\"input\"\"\n\"\n"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+const TEMPLATE: &str = r#"use nom::IResult;
+
+use crate::{
+    solution::{Sample, Solution},
+    utils::{
+        parse::{lines_of, unsigned},
+        parse_all, ParseError,
+    },
+};
+
+fn parse(s: &str) -> IResult<&str, Vec<u64>> {
+    lines_of(unsigned)(s)
+}
+
+const SAMPLE: &str = "
+        1
+        2
+        3";
+
+pub struct Day{N};
+
+impl Solution for Day{N} {
+    const DAY: u32 = {N};
+    const SAMPLE: Sample = Sample {
+        input: SAMPLE,
+        part1: Some("6"),
+        part2: None,
+    };
+
+    type Input = Vec<u64>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(s: &str) -> Result<Self::Input, ParseError> {
+        parse_all(s, parse)
+    }
+
+    fn part1(nums: &Self::Input) -> u64 {
+        nums.iter().sum()
+    }
+
+    fn part2(_nums: &Self::Input) -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sample() {
+        assert_eq!(Day{N}::part1(&Day{N}::parse(SAMPLE).unwrap()), 6);
+    }
+
+    #[test]
+    fn test_sample_2() {
+        assert_eq!(Day{N}::part2(&Day{N}::parse(SAMPLE).unwrap()), None);
+    }
+}
+"#;
+
+fn render(day: u32) -> String {
+    TEMPLATE.replace("{N}", &day.to_string())
+}
+
+fn already_exists(what: impl std::fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!("{what} already exists"),
+    )
+}
+
+/// Adds `pub mod day{day};` to the module list in `lib.rs`, keeping it sorted.
+fn register_module(lib: &str, day: u32) -> io::Result<String> {
+    let line = format!("pub mod day{day};");
+    let mut lines: Vec<_> = lib.lines().collect();
+    if lines.contains(&line.as_str()) {
+        return Err(already_exists(format!("module day{day}")));
+    }
+    let is_mod = |l: &str| l.starts_with("pub mod ") && l.ends_with(';');
+    let start = lines.iter().position(|l| is_mod(l)).unwrap_or(lines.len());
+    let end = start + lines[start..].iter().take_while(|l| is_mod(l)).count();
+    lines.insert(end, &line);
+    lines[start..=end].sort_by_key(|l| l.trim_end_matches(';'));
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Adds the day to the `DAYS` registry in `solution.rs`, keeping it in day order.
+fn register_day(solution: &str, day: u32) -> io::Result<String> {
+    let entry = format!(
+        "    Day::new::<crate::day{day}::Day{day}>(include_str!(\"../inputs/{day}.txt\")),"
+    );
+    let mut lines: Vec<_> = solution.lines().collect();
+    let start = lines
+        .iter()
+        .position(|l| l.starts_with("pub const DAYS"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no DAYS registry"))?
+        + 1;
+    let len = lines[start..].iter().take_while(|l| **l != "];").count();
+    let day_of = |l: &str| -> Option<u32> {
+        let rest = l.trim().strip_prefix("Day::new::<crate::day")?;
+        rest.split(':').next()?.parse().ok()
+    };
+    if lines[start..start + len]
+        .iter()
+        .any(|l| day_of(l) == Some(day))
+    {
+        return Err(already_exists(format!("day {day} in DAYS")));
+    }
+    let at = start
+        + lines[start..start + len]
+            .iter()
+            .take_while(|l| day_of(l).is_none_or(|d| d < day))
+            .count();
+    lines.insert(at, &entry);
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Creates `src/day{day}.rs` and registers the day, along with an empty
+/// `inputs/{day}.txt` unless `fetch` has already put the input there.
+/// Nothing is written if the module or its registration already exists.
+pub fn new_day(root: &Path, day: u32) -> io::Result<Vec<PathBuf>> {
+    let module = root.join("src").join(format!("day{day}.rs"));
+    let input = root.join("inputs").join(format!("{day}.txt"));
+    let lib = root.join("src").join("lib.rs");
+    let solution = root.join("src").join("solution.rs");
+    if module.exists() {
+        return Err(already_exists(module.display()));
+    }
+    let new_lib = register_module(&fs::read_to_string(&lib)?, day)?;
+    let new_solution = register_day(&fs::read_to_string(&solution)?, day)?;
+    fs::write(&module, render(day))?;
+    let mut written = vec![module];
+    if !input.exists() {
+        fs::write(&input, "")?;
+        written.push(input);
+    }
+    fs::write(&lib, new_lib)?;
+    fs::write(&solution, new_solution)?;
+    written.extend([lib, solution]);
+    Ok(written)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let module = render(12);
+        assert!(module.contains("pub struct Day12;"));
+        assert!(module.contains("const DAY: u32 = 12;"));
+        assert!(!module.contains("{N}"));
+    }
+
+    #[test]
+    fn test_register_module() {
+        let lib = "pub mod bench;\npub mod day1;\npub mod day10;\npub mod day9;\npub mod utils;\n";
+        assert_eq!(
+            register_module(lib, 11).unwrap(),
+            "pub mod bench;\npub mod day1;\npub mod day10;\npub mod day11;\npub mod day9;\npub mod utils;\n"
+        );
+        assert!(register_module(lib, 9).is_err());
+    }
+
+    #[test]
+    fn test_register_day() {
+        let solution = "\
+pub const DAYS: &[Day] = &[
+    Day::new::<crate::day1::Day1>(include_str!(\"../inputs/1.txt\")),
+    Day::new::<crate::day3::Day3>(include_str!(\"../inputs/3.txt\")),
+];
+";
+        assert_eq!(
+            register_day(solution, 2).unwrap(),
+            "\
+pub const DAYS: &[Day] = &[
+    Day::new::<crate::day1::Day1>(include_str!(\"../inputs/1.txt\")),
+    Day::new::<crate::day2::Day2>(include_str!(\"../inputs/2.txt\")),
+    Day::new::<crate::day3::Day3>(include_str!(\"../inputs/3.txt\")),
+];
+"
+        );
+        assert!(register_day(solution, 3).is_err());
+    }
+
+    #[test]
+    fn test_new_day_keeps_input() {
+        let root = std::env::temp_dir().join(format!("aoc-scaffold-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("inputs")).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub mod day1;\n").unwrap();
+        fs::write(
+            root.join("src/solution.rs"),
+            "pub const DAYS: &[Day] = &[\n];\n",
+        )
+        .unwrap();
+        fs::write(root.join("inputs/2.txt"), "fetched\n").unwrap();
+        let written = new_day(&root, 2).unwrap();
+        assert!(!written.contains(&root.join("inputs/2.txt")));
+        assert_eq!(
+            fs::read_to_string(root.join("inputs/2.txt")).unwrap(),
+            "fetched\n"
+        );
+        assert!(root.join("src/day2.rs").exists());
+        assert!(new_day(&root, 2).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,241 @@
+use std::{any::Any, error, fmt};
+
+use crate::{
+    answer::{Answer, IntoAnswer},
+    answers::Verdict,
+    generate::{Rng, Size},
+    utils::{ParseError, SolveError},
+};
+
+/// A worked example from the puzzle text and the answers it should give.
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub input: &'static str,
+    /// Integers are written as they are, text as it appears in the puzzle.
+    /// `None` where the example does not apply to the part as solved.
+    pub part1: Option<&'static str>,
+    pub part2: Option<&'static str>,
+}
+
+impl Sample {
+    pub fn expected(&self, part: u32) -> Option<Answer> {
+        let expected = match part {
+            1 => self.part1,
+            2 => self.part2,
+            _ => None,
+        }?;
+        Some(Answer::parse_int(expected).unwrap_or_else(|| Answer::text(expected)))
+    }
+
+    pub fn check(&self, part: u32, answer: &Answer) -> Verdict {
+        match self.expected(part) {
+            Some(expected) if expected == *answer => Verdict::Pass,
+            Some(expected) => Verdict::Fail { expected },
+            None => Verdict::Unknown,
+        }
+    }
+}
+
+/// A day's puzzle: one parse step shared by both parts.
+pub trait Solution {
+    const DAY: u32;
+    const SAMPLE: Sample;
+
+    type Input;
+    type Answer1: IntoAnswer;
+    type Answer2: IntoAnswer;
+
+    fn parse(s: &str) -> Result<Self::Input, ParseError>;
+
+    fn part1(input: &Self::Input) -> Self::Answer1;
+
+    /// Days without a second part leave this as `None`.
+    fn part2(_input: &Self::Input) -> Option<Self::Answer2> {
+        None
+    }
+
+    /// A random input in the format `parse` reads, keeping to what the parts
+    /// rely on. Days without a generator leave this as `None`.
+    fn generate(_rng: &mut Rng, _size: Size) -> Option<String> {
+        None
+    }
+}
+
+/// A parsed input whose type is only known to its day's `Solution`.
+pub type Parsed = Box<dyn Any + Send + Sync>;
+
+/// A type-erased `Solution`, so that days can be iterated over generically.
+pub struct Day {
+    pub day: u32,
+    /// The input embedded at compile time.
+    pub embedded: &'static str,
+    pub sample: Sample,
+    parse: fn(&str) -> Result<Parsed, ParseError>,
+    part1: fn(&Parsed) -> Result<Answer, SolveError>,
+    part2: fn(&Parsed) -> Option<Result<Answer, SolveError>>,
+    generate: fn(&mut Rng, Size) -> Option<String>,
+}
+
+/// Why `Day::run` gave no answer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    Parse(ParseError),
+    Solve(SolveError),
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+impl From<SolveError> for Error {
+    fn from(e: SolveError) -> Self {
+        Error::Solve(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(e) => write!(f, "parse error: {e}"),
+            Error::Solve(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+fn parse<S: Solution>(s: &str) -> Result<Parsed, ParseError>
+where
+    S::Input: Send + Sync + 'static,
+{
+    Ok(Box::new(S::parse(s)?))
+}
+
+fn input<S: Solution>(parsed: &Parsed) -> &S::Input
+where
+    S::Input: 'static,
+{
+    parsed
+        .downcast_ref()
+        .expect("input was parsed by another day")
+}
+
+fn part1<S: Solution>(parsed: &Parsed) -> Result<Answer, SolveError>
+where
+    S::Input: 'static,
+{
+    S::part1(input::<S>(parsed)).into_answer()
+}
+
+fn part2<S: Solution>(parsed: &Parsed) -> Option<Result<Answer, SolveError>>
+where
+    S::Input: 'static,
+{
+    S::part2(input::<S>(parsed)).map(IntoAnswer::into_answer)
+}
+
+impl Day {
+    pub const fn new<S: Solution>(embedded: &'static str) -> Self
+    where
+        S::Input: Send + Sync + 'static,
+    {
+        Self {
+            day: S::DAY,
+            embedded,
+            sample: S::SAMPLE,
+            parse: parse::<S>,
+            part1: part1::<S>,
+            part2: part2::<S>,
+            generate: S::generate,
+        }
+    }
+
+    pub fn parse(&self, s: &str) -> Result<Parsed, ParseError> {
+        (self.parse)(s)
+    }
+
+    /// Solves `part` of an input parsed by this day, or `None` if there is no such part.
+    pub fn solve(&self, parsed: &Parsed, part: u32) -> Result<Option<Answer>, SolveError> {
+        match part {
+            1 => (self.part1)(parsed).map(Some),
+            2 => (self.part2)(parsed).transpose(),
+            _ => Ok(None),
+        }
+    }
+
+    /// A random input of about `size`, the same for the same `seed`, or
+    /// `None` if this day has no generator.
+    pub fn generate(&self, size: Size, seed: u64) -> Option<String> {
+        (self.generate)(&mut Rng::new(seed), size)
+    }
+
+    /// Parses `s` and solves `part` of it.
+    pub fn run(&self, s: &str, part: u32) -> Result<Option<Answer>, Error> {
+        Ok(self.solve(&self.parse(s)?, part)?)
+    }
+}
+
+/// Every day, in order.
+pub const DAYS: &[Day] = &[
+    Day::new::<crate::day1::Day1>(include_str!("../inputs/1.txt")),
+    Day::new::<crate::day2::Day2>(include_str!("../inputs/2.txt")),
+    Day::new::<crate::day3::Day3>(include_str!("../inputs/3.txt")),
+    Day::new::<crate::day4::Day4>(include_str!("../inputs/4.txt")),
+    Day::new::<crate::day5::Day5>(include_str!("../inputs/5.txt")),
+    Day::new::<crate::day6::Day6>(include_str!("../inputs/6.txt")),
+    Day::new::<crate::day7::Day7>(include_str!("../inputs/7.txt")),
+    Day::new::<crate::day8::Day8>(include_str!("../inputs/8.txt")),
+    Day::new::<crate::day9::Day9>(include_str!("../inputs/9.txt")),
+    Day::new::<crate::day10::Day10>(include_str!("../inputs/10.txt")),
+];
+
+pub fn find(day: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_days_in_order() {
+        assert!(DAYS.windows(2).all(|w| w[0].day < w[1].day));
+    }
+
+    #[test]
+    fn test_samples() {
+        for day in DAYS {
+            let parsed = day.parse(day.sample.input).unwrap();
+            for part in [1, 2] {
+                if let Some(expected) = day.sample.expected(part) {
+                    assert_eq!(day.solve(&parsed, part), Ok(Some(expected)));
+                }
+            }
+        }
+    }
+
+    /// Days without a generator are skipped, as writing one is optional.
+    #[test]
+    fn test_generated() {
+        let size = Size {
+            count: Some(20),
+            width: Some(4),
+        };
+        for day in DAYS {
+            for seed in 0..5 {
+                let Some(input) = day.generate(size, seed) else {
+                    break;
+                };
+                assert_eq!(day.generate(size, seed).as_ref(), Some(&input));
+                let parsed = day.parse(&input).unwrap_or_else(|e| panic!("{input}\n{e}"));
+                for part in [1, 2] {
+                    if let Err(e) = day.solve(&parsed, part) {
+                        panic!("day {} part {part}: {e}\n{input}", day.day);
+                    }
+                }
+            }
+        }
+    }
+}
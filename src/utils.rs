@@ -1,86 +1,223 @@
-macro_rules! make_runner {
-    (@helper
-        { $($mods:tt)* }
-        { $($labels:tt)* }
-        { $($arms:tt)* }
-        $day:tt,
-        $($rest:tt)*
-    ) => (
-        paste::paste! { crate::utils::make_runner!(@helper
-            {
-                $($mods)*
-                mod [<day $day>];
-            }
-            {
-                [< Day $day >],
-                $($labels)*
-            }
-            {
-                Task::[< Day $day >] => {
-                    let input = include_str!(concat!("../inputs/", $day, ".txt"));
-                    (stringify!($day), [< day $day >]::solve(input).to_string())
-                },
-                $($arms)*
-            }
-            $($rest)*
-        ); }
-    );
-    (@helper
-        { $($mods:tt)* }
-        { $($labels:tt)* }
-        { $($arms:tt)* }
-        $day:tt +,
-        $($rest:tt)*
-    ) => (
-        paste::paste! { crate::utils::make_runner!(@helper
-            {
-                $($mods)*
-                mod [<day $day>];
-            }
-            {
-                [< Day $day _2 >],
-                [< Day $day >],
-                $($labels)*
-            }
-            {
-                Task::[< Day $day _2 >] => {
-                    let input = include_str!(concat!("../inputs/", $day, ".txt"));
-                    (concat!($day, " (part 2)"), [< day $day >]::solve_2(input).to_string())
-                },
-                Task::[< Day $day >] => {
-                    let input = include_str!(concat!("../inputs/", $day, ".txt"));
-                    (stringify!($day), [< day $day >]::solve(input).to_string())
-                },
-                $($arms)*
-            }
-            $($rest)*
-        ); }
-    );
-    (@helper
-        { $($mods:tt)* }
-        { $($labels:tt)* }
-        { $lhs:path => $rhs:expr, $($rest_lhs:path => $rest_rhs:expr,)* }
-    ) => (
-        #[derive(clap::ValueEnum, Copy, Clone, Debug)]
-        enum Task { $($labels)* Latest }
-
-        $($mods)*
-
-        fn run(args: Args) {
-            let start = std::time::Instant::now();
-            let (day, result) = match args.task {
-                $lhs => $rhs,
-                Task::Latest => $rhs,
-                $($rest_lhs => $rest_rhs,)*
-            };
-            let duration = start.elapsed().as_secs_f32();
-            println!("Computed result for day {day} in {duration:.3} seconds: {result}");
+pub mod compressor;
+pub mod discrete;
+pub mod grid;
+pub mod interval_set;
+pub mod log;
+pub mod parse;
+pub mod union_find;
+
+use std::{
+    error::Error,
+    fmt, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use nom::IResult;
+
+pub use compressor::Compressor;
+pub use discrete::Discrete;
+pub use grid::Grid;
+pub use interval_set::IntervalSet;
+pub use union_find::{KeyedUnionFind, UnionFind};
+
+/// Where the input for `day` lives in the source tree.
+pub fn input_path(day: u32) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("inputs")
+        .join(format!("{day}.txt"))
+}
+
+/// Reads the puzzle input for `day`.
+///
+/// An explicit `path` always wins, with `-` meaning stdin. Otherwise the
+/// current `inputs/{day}.txt` is read from disk, so that editing it does not
+/// need a rebuild, and the copy embedded at compile time is the fallback.
+pub fn load_input(path: Option<&Path>, day: u32, embedded: &'static str) -> io::Result<String> {
+    match path {
+        Some(path) if path == Path::new("-") => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
         }
-    );
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display()))),
+        None => Ok(fs::read_to_string(input_path(day)).unwrap_or_else(|_| embedded.to_string())),
+    }
+}
 
-    ($($day:tt)*) => {
-        crate::utils::make_runner!(@helper {} {} {} $($day)*);
-    };
+/// Why a puzzle input could not be parsed, and where.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line of the offending text.
+    pub line: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+    /// The offending text, up to the end of its line.
+    pub text: String,
+    pub message: String,
+}
+
+/// A parsed value along with its text, so that checks made after parsing
+/// can point at it with `ParseError::at`.
+pub type Spanned<'a, T> = (&'a str, T);
+
+/// The text of `input` before `rest`, which must be a subslice of it.
+fn before<'a>(input: &'a str, rest: &str) -> &'a str {
+    let offset = rest.as_ptr() as usize - input.as_ptr() as usize;
+    assert!(offset <= input.len(), "position outside of the input");
+    &input[..offset]
+}
+
+/// The 1-based line of `input` that `rest`, a subslice of it, starts on.
+pub fn line_of(input: &str, rest: &str) -> usize {
+    before(input, rest).matches('\n').count() + 1
+}
+
+impl ParseError {
+    /// Points at `rest`, which must be a subslice of `input`.
+    pub fn at(input: &str, rest: &str, message: impl Into<String>) -> Self {
+        let before = before(input, rest);
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            line: line_of(input, rest),
+            column: before[line_start..].chars().count() + 1,
+            text: rest.lines().next().unwrap_or_default().to_string(),
+            message: message.into(),
+        }
+    }
+
+    fn from_nom(input: &str, error: nom::error::Error<&str>) -> Self {
+        use nom::error::ErrorKind;
+        // The remaining kinds come from parsers of single characters, such as
+        // `satisfy`, `map_opt` and `alt`, whose names mean nothing to a reader.
+        let message = match error.code {
+            ErrorKind::Digit => "expected a number",
+            ErrorKind::MapRes => "number out of range",
+            ErrorKind::Count => "wrong number of values",
+            ErrorKind::CrLf => "expected the end of the line",
+            _ if error.input.is_empty() => "unexpected end of input",
+            ErrorKind::Eof => "unexpected input",
+            _ => "unexpected character",
+        };
+        Self::at(input, error.input, message)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )?;
+        match self.text.as_str() {
+            "" => write!(f, " at end of line"),
+            text => write!(f, " at `{text}`"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Why a solver gave up on an input that parsed, such as an answer that
+/// overflows or a target that cannot be reached.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolveError(pub String);
+
+impl SolveError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for SolveError {}
+
+/// Runs `parser` over all of `input`, so that anything it leaves behind
+/// other than trailing whitespace is an error rather than silently dropped.
+pub fn parse_all<'a, O>(
+    input: &'a str,
+    parser: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> Result<O, ParseError> {
+    match parse::complete(parser)(input) {
+        Ok((_, output)) => Ok(output),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(ParseError::from_nom(input, e)),
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::at(input, "", "incomplete input")),
+    }
 }
 
-pub(crate) use make_runner;
+/// Maps `f` over `items` on up to `jobs` threads, keeping the results in order.
+pub fn parallel_map<T: Sync, R: Send>(
+    jobs: usize,
+    items: &[T],
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new(items.iter().map(|_| None).collect());
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(i) else {
+                    break;
+                };
+                let result = f(item);
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|r| r.expect("every item is mapped"))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use nom::{
+        character::complete::{digit1, multispace0},
+        multi::many0,
+        sequence::preceded,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_parse_all() {
+        let input = "12\n34\n5x6\n78\n";
+        let result = parse_all(input, many0(preceded(multispace0, digit1)));
+        let error = result.unwrap_err();
+        assert_eq!((error.line, error.column), (3, 2));
+        assert_eq!(error.text, "x6");
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 2: unexpected input at `x6`"
+        );
+        let result = parse_all("1 2 \n", many0(preceded(multispace0, digit1)));
+        assert_eq!(result, Ok(vec!["1", "2"]));
+    }
+
+    #[test]
+    fn test_parallel_map() {
+        let items: Vec<u64> = (0..100).collect();
+        for jobs in [0, 1, 4, 200] {
+            assert_eq!(
+                parallel_map(jobs, &items, |&i| i * i),
+                items.iter().map(|i| i * i).collect::<Vec<_>>()
+            );
+        }
+    }
+}
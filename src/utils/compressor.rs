@@ -0,0 +1,143 @@
+use std::ops::RangeInclusive;
+
+use super::Discrete;
+
+/// Maps the distinct values along one axis to consecutive indices.
+///
+/// Index `i` stands for a cell: the values from the `i`th key up to the next
+/// one, or just the last key for the last index. Without gaps, values with
+/// room between them land in neighbouring cells; with gaps, each key has a
+/// cell to itself and the room between keys gets a cell of its own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Compressor<T> {
+    keys: Vec<T>,
+}
+
+impl<T: Ord + Copy> Compressor<T> {
+    pub fn new(values: impl IntoIterator<Item = T>) -> Self {
+        let mut keys: Vec<_> = values.into_iter().collect();
+        keys.sort();
+        keys.dedup();
+        Self { keys }
+    }
+
+    /// A compressor per axis for points with `N` coordinates.
+    pub fn axes<const N: usize>(points: impl IntoIterator<Item = [T; N]>) -> [Self; N] {
+        split(points).map(Self::new)
+    }
+
+    /// The number of cells.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The cell holding `value`, or `None` outside of the keys.
+    pub fn compress(&self, value: T) -> Option<usize> {
+        if self.keys.last().is_none_or(|&last| value > last) {
+            return None;
+        }
+        self.keys.partition_point(|&k| k <= value).checked_sub(1)
+    }
+
+    /// The first value in cell `i`.
+    pub fn decompress(&self, i: usize) -> T {
+        self.keys[i]
+    }
+
+    /// The cells from the one holding the start of `range` to the one
+    /// holding its end, or `None` if either end is outside of the keys.
+    pub fn compress_range(&self, range: RangeInclusive<T>) -> Option<RangeInclusive<usize>> {
+        Some(self.compress(*range.start())?..=self.compress(*range.end())?)
+    }
+
+    /// The values from the start of the first cell to the start of the
+    /// last, which undoes `compress_range` on keys.
+    pub fn decompress_range(&self, range: RangeInclusive<usize>) -> RangeInclusive<T> {
+        self.decompress(*range.start())..=self.decompress(*range.end())
+    }
+}
+
+impl<T: Discrete> Compressor<T> {
+    /// Like `new`, with a key right after each value that is not followed by
+    /// the next one, so that the room between them keeps a cell.
+    pub fn with_gaps(values: impl IntoIterator<Item = T>) -> Self {
+        let keys = Self::new(values).keys;
+        let mut gapped = Vec::with_capacity(2 * keys.len());
+        for (i, &key) in keys.iter().enumerate() {
+            gapped.push(key);
+            match (key.successor(), keys.get(i + 1)) {
+                (Some(gap), Some(&next)) if gap < next => gapped.push(gap),
+                _ => {}
+            }
+        }
+        Self { keys: gapped }
+    }
+
+    /// Like `axes`, with gaps on every axis.
+    pub fn axes_with_gaps<const N: usize>(points: impl IntoIterator<Item = [T; N]>) -> [Self; N] {
+        split(points).map(Self::with_gaps)
+    }
+}
+
+/// The values along each axis.
+fn split<T, const N: usize>(points: impl IntoIterator<Item = [T; N]>) -> [Vec<T>; N] {
+    let mut axes = std::array::from_fn(|_| Vec::new());
+    for point in points {
+        for (axis, value) in axes.iter_mut().zip(point) {
+            axis.push(value);
+        }
+    }
+    axes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_duplicates() {
+        let axis = Compressor::new([30, 10, 20, 10, 30]);
+        assert_eq!(axis.len(), 3);
+        assert_eq!(axis.compress(10), Some(0));
+        assert_eq!(axis.compress(30), Some(2));
+        assert_eq!(axis.decompress(1), 20);
+        // Values between keys belong to the cell of the key before them.
+        assert_eq!(axis.compress(25), Some(1));
+        assert_eq!(axis.compress(9), None);
+        assert_eq!(axis.compress(31), None);
+    }
+
+    #[test]
+    fn test_gaps() {
+        let axis = Compressor::with_gaps([5, 1, 2, 9, 5]);
+        // 1 and 2 are neighbours; 3..=4 and 6..=8 each become a gap.
+        assert_eq!(axis.keys, [1, 2, 3, 5, 6, 9]);
+        assert_eq!(axis.compress(4), Some(2));
+        assert_eq!(axis.compress(5), Some(3));
+        assert_eq!(axis.compress(8), Some(4));
+        assert_eq!(Compressor::with_gaps([u8::MAX, 0]).keys, [0, 1, u8::MAX]);
+        assert_eq!(Compressor::<i32>::with_gaps([]).len(), 0);
+    }
+
+    #[test]
+    fn test_ranges() {
+        let axis = Compressor::with_gaps([1, 2, 5, 9]);
+        assert_eq!(axis.compress_range(2..=9), Some(1..=5));
+        assert_eq!(axis.decompress_range(1..=5), 2..=9);
+        assert_eq!(axis.compress_range(4..=7), Some(2..=4));
+        assert_eq!(axis.compress_range(0..=7), None);
+    }
+
+    #[test]
+    fn test_axes() {
+        let [xs, ys] = Compressor::axes_with_gaps([[7, 1], [11, 1], [11, 7], [9, 7]]);
+        assert_eq!(xs.keys, [7, 8, 9, 10, 11]);
+        assert_eq!(ys.keys, [1, 2, 7]);
+        let [zs] = Compressor::axes([[3], [3]]);
+        assert_eq!(zs.len(), 1);
+    }
+}
@@ -0,0 +1,51 @@
+/// Values that come one after another, like integers, so that sets of them
+/// can tell neighbours apart from values with room between them.
+pub trait Discrete: Ord + Copy {
+    /// The next value, or `None` at the top of the type.
+    fn successor(self) -> Option<Self>;
+
+    /// The previous value, or `None` at the bottom of the type.
+    fn predecessor(self) -> Option<Self>;
+
+    /// How many steps up from `start` this is, if that fits in a `u64`.
+    fn steps_from(self, start: Self) -> Option<u64>;
+}
+
+macro_rules! discrete {
+    ($($t:ty),*) => {
+        $(impl Discrete for $t {
+            fn successor(self) -> Option<Self> {
+                self.checked_add(1)
+            }
+
+            fn predecessor(self) -> Option<Self> {
+                self.checked_sub(1)
+            }
+
+            fn steps_from(self, start: Self) -> Option<u64> {
+                match self >= start {
+                    true => u64::try_from(self.abs_diff(start)).ok(),
+                    false => None,
+                }
+            }
+        })*
+    };
+}
+
+discrete!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ends() {
+        assert_eq!(u8::MAX.successor(), None);
+        assert_eq!(i8::MIN.predecessor(), None);
+        assert_eq!(0u8.predecessor(), None);
+        assert_eq!(5i32.successor(), Some(6));
+        assert_eq!(i8::MAX.steps_from(i8::MIN), Some(255));
+        assert_eq!(u128::MAX.steps_from(0), None);
+        assert_eq!(3u64.steps_from(4), None);
+    }
+}
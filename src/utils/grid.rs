@@ -0,0 +1,241 @@
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use super::ParseError;
+
+/// A dense rectangular grid, indexed by `(y, x)` from the top left like the
+/// puzzles' character maps.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    height: usize,
+    width: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(height: usize, width: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            height,
+            width,
+            cells: vec![fill; height * width],
+        }
+    }
+
+    /// Reads a character map, one row per line, with whitespace around the
+    /// map and each line ignored so that indented examples parse too.
+    ///
+    /// `f` turns each character into a cell, or explains what was expected
+    /// instead, and every row must be as wide as the first.
+    pub fn parse<E: Into<String>>(
+        s: &str,
+        mut f: impl FnMut(char) -> Result<T, E>,
+    ) -> Result<Self, ParseError> {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for line in s.trim().lines() {
+            let line = line.trim();
+            let start = cells.len();
+            for (i, c) in line.char_indices() {
+                cells.push(f(c).map_err(|e| ParseError::at(s, &line[i..], e))?);
+            }
+            let len = cells.len() - start;
+            if *width.get_or_insert(len) != len {
+                let message = format!("expected {} places, as in the first row", width.unwrap());
+                return Err(ParseError::at(s, line, message));
+            }
+            height += 1;
+        }
+        Ok(Self {
+            height,
+            width: width.unwrap_or(0),
+            cells,
+        })
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The cell at `(y, x)`, or `None` off the grid.
+    pub fn get(&self, (y, x): (usize, usize)) -> Option<&T> {
+        (y < self.height && x < self.width).then(|| &self.cells[y * self.width + x])
+    }
+
+    pub fn get_mut(&mut self, (y, x): (usize, usize)) -> Option<&mut T> {
+        (y < self.height && x < self.width).then(|| &mut self.cells[y * self.width + x])
+    }
+
+    /// Every position, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (y, x)))
+    }
+
+    /// Every position with its cell, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(&self.cells)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on a width of 0, which only an empty grid has.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(
+            x < self.width,
+            "column {x} outside of a grid {} wide",
+            self.width
+        );
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    /// The neighbours above, left, right and below that are on the grid.
+    pub fn neighbours4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offsets(pos, &[(-1, 0), (0, -1), (0, 1), (1, 0)])
+    }
+
+    /// The neighbours on the grid, diagonals included, row by row.
+    pub fn neighbours8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        const OFFSETS: [(isize, isize); 8] = [
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+            (0, -1),
+            (0, 1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+        ];
+        self.offsets(pos, &OFFSETS)
+    }
+
+    fn offsets(
+        &self,
+        (y, x): (usize, usize),
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        offsets.iter().filter_map(move |&(dy, dx)| {
+            let pos = (y.checked_add_signed(dy)?, x.checked_add_signed(dx)?);
+            self.get(pos).map(|_| pos)
+        })
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            height: self.height,
+            width: self.width,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &T {
+        let (height, width) = (self.height, self.width);
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} outside of a {height}x{width} grid"))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut T {
+        let (height, width) = (self.height, self.width);
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} outside of a {height}x{width} grid"))
+    }
+}
+
+/// Writes the grid back out as text, one line per row.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            row.iter().try_for_each(|cell| write!(f, "{cell}"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn digits(s: &str) -> Result<Grid<u32>, ParseError> {
+        Grid::parse(s, |c| c.to_digit(10).ok_or("expected a digit"))
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = digits(
+            "
+            123
+            456",
+        )
+        .unwrap();
+        assert_eq!((grid.height(), grid.width()), (2, 3));
+        assert_eq!(grid[(1, 0)], 4);
+        assert_eq!(grid.get((0, 3)), None);
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.to_string(), "123\n456");
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let error = digits("123\n4x6").err().unwrap();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (2, 2, "x6")
+        );
+        let error = digits("123\n45").err().unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+        let empty = digits("").unwrap();
+        assert_eq!(
+            (empty.height(), empty.width(), empty.rows().count()),
+            (0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_views() {
+        let grid = digits("123\n456").unwrap();
+        assert_eq!(grid.row(1), [4, 5, 6]);
+        assert_eq!(grid.rows().collect::<Vec<_>>(), [[1, 2, 3], [4, 5, 6]]);
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), [2, 5]);
+        let doubled = grid.map(|n| n * 2);
+        assert_eq!(doubled.iter().last(), Some(((1, 2), &12)));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::new(3, 3, '.');
+        assert_eq!(grid.neighbours4((1, 1)).count(), 4);
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            [(0, 1), (1, 0)]
+        );
+        assert_eq!(
+            grid.neighbours8((2, 2)).collect::<Vec<_>>(),
+            [(1, 1), (1, 2), (2, 1)]
+        );
+    }
+}
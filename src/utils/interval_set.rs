@@ -0,0 +1,241 @@
+use std::ops::RangeInclusive;
+
+use itertools::Itertools;
+
+use super::Discrete;
+
+/// A set of values stored as inclusive ranges, kept sorted and with no two
+/// of them overlapping or touching, so that each set has one layout.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    ranges: Vec<(T, T)>,
+}
+
+impl<T: Discrete> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    /// Whether `value` is in the set, by binary search over the ranges.
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.ranges.partition_point(|&(_, end)| end < value);
+        self.ranges.get(i).is_some_and(|&(start, _)| start <= value)
+    }
+
+    /// Adds every value in `range`, merging it with the ranges it overlaps
+    /// or touches.
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        let (mut start, mut end) = range.into_inner();
+        if start > end {
+            return;
+        }
+        let touches_start = |&(_, e): &(T, T)| e.successor().is_some_and(|s| s < start);
+        let i = self.ranges.partition_point(touches_start);
+        let j = self
+            .ranges
+            .partition_point(|&(s, _)| s <= end || end.successor() == Some(s));
+        if i < j {
+            start = start.min(self.ranges[i].0);
+            end = end.max(self.ranges[j - 1].1);
+        }
+        self.ranges.splice(i..j, [(start, end)]);
+    }
+
+    /// Takes every value in `range` out, splitting the ranges it falls inside.
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        let (start, end) = range.into_inner();
+        if start > end {
+            return;
+        }
+        let i = self.ranges.partition_point(|&(_, e)| e < start);
+        let j = self.ranges.partition_point(|&(s, _)| s <= end);
+        if i == j {
+            return;
+        }
+        let (first, last) = (self.ranges[i].0, self.ranges[j - 1].1);
+        // Neither end can run off the type, as values lie beyond each.
+        let before = (first < start).then(|| (first, start.predecessor().unwrap()));
+        let after = (last > end).then(|| (end.successor().unwrap(), last));
+        self.ranges.splice(i..j, before.into_iter().chain(after));
+    }
+
+    /// How many values are in the set, or `None` if that overflows a `u64`.
+    pub fn len(&self) -> Option<u64> {
+        self.ranges.iter().try_fold(0u64, |len, &(start, end)| {
+            len.checked_add(end.steps_from(start)?)?.checked_add(1)
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The ranges, in order.
+    pub fn iter(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.iter().map(|&(start, end)| start..=end)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut ranges = Vec::with_capacity(self.ranges.len() + other.ranges.len());
+        for &range in self.ranges.iter().merge(&other.ranges) {
+            push_merged(&mut ranges, range);
+        }
+        Self { ranges }
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+        let mut ranges = Vec::new();
+        // Pieces of ranges that are apart are apart too, so they need no merging.
+        while let (Some(&&(a_start, a_end)), Some(&&(b_start, b_end))) = (a.peek(), b.peek()) {
+            let (start, end) = (a_start.max(b_start), a_end.min(b_end));
+            if start <= end {
+                ranges.push((start, end));
+            }
+            if a_end < b_end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut difference = self.clone();
+        other.iter().for_each(|range| difference.remove(range));
+        difference
+    }
+}
+
+/// Adds `range` after the ranges so far, none of which start after it,
+/// merging it into the last one if they overlap or touch.
+fn push_merged<T: Discrete>(ranges: &mut Vec<(T, T)>, (start, end): (T, T)) {
+    match ranges.last_mut() {
+        Some(last) if last.1.successor().is_none_or(|next| start <= next) => {
+            last.1 = last.1.max(end);
+        }
+        _ => ranges.push((start, end)),
+    }
+}
+
+/// Sorts the new ranges in with the old ones and merges them in one pass,
+/// rather than inserting them one at a time.
+impl<T: Discrete> Extend<RangeInclusive<T>> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = RangeInclusive<T>>>(&mut self, ranges: I) {
+        let mut all = std::mem::take(&mut self.ranges);
+        all.extend(
+            ranges
+                .into_iter()
+                .map(RangeInclusive::into_inner)
+                .filter(|(start, end)| start <= end),
+        );
+        all.sort_unstable();
+        for range in all {
+            push_merged(&mut self.ranges, range);
+        }
+    }
+}
+
+impl<T: Discrete> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(ranges: I) -> Self {
+        let mut set = Self::new();
+        set.extend(ranges);
+        set
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn set(ranges: &[(u8, u8)]) -> IntervalSet<u8> {
+        ranges.iter().map(|&(l, h)| l..=h).collect()
+    }
+
+    /// Every value in the set, one at a time, after checking that its
+    /// ranges are in order and apart.
+    fn values(set: &IntervalSet<u8>) -> BTreeSet<u8> {
+        let apart = |w: &[(u8, u8)]| w[0].1.successor() < Some(w[1].0);
+        assert!(set.ranges.iter().all(|&(l, h)| l <= h));
+        assert!(set.ranges.windows(2).all(apart));
+        set.iter().flatten().collect()
+    }
+
+    fn ranges() -> impl Strategy<Value = Vec<(u8, u8)>> {
+        prop::collection::vec((any::<u8>(), 0..40u8), 0..8).prop_map(|ranges| {
+            ranges
+                .into_iter()
+                .map(|(l, len)| (l, l.saturating_add(len)))
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_normalised() {
+        assert_eq!(set(&[(5, 9), (1, 5)]).ranges, [(1, 9)]);
+        assert_eq!(set(&[(1, 3), (4, 6), (9, 9)]).ranges, [(1, 6), (9, 9)]);
+        assert_eq!(set(&[(9, 7)]), IntervalSet::new());
+        assert_eq!(set(&[(250, 255), (0, 3), (2, 254)]).ranges, [(0, 255)]);
+        let mut extended = set(&[(1, 3), (20, 30)]);
+        extended.extend([25..=40, 4..=5, 7..=7]);
+        assert_eq!(extended.ranges, [(1, 5), (7, 7), (20, 40)]);
+    }
+
+    #[test]
+    fn test_contains() {
+        let set = set(&[(1, 3), (7, 9)]);
+        assert!([1, 2, 3, 7, 9].into_iter().all(|v| set.contains(v)));
+        assert!(![0, 4, 6, 10].into_iter().any(|v| set.contains(v)));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set = set(&[(0, 255)]);
+        set.remove(10..=19);
+        set.remove(255..=255);
+        assert_eq!(set.ranges, [(0, 9), (20, 254)]);
+        set.remove(0..=254);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_len() {
+        assert_eq!(set(&[(1, 3), (7, 9)]).len(), Some(6));
+        assert_eq!(set(&[(0, 255)]).len(), Some(256));
+        let every_u64: IntervalSet<u64> = [0..=u64::MAX].into_iter().collect();
+        assert_eq!(every_u64.len(), None);
+    }
+
+    proptest! {
+        #[test]
+        fn test_naive(a in ranges(), b in ranges(), removed in ranges()) {
+            let (a_set, b_set) = (set(&a), set(&b));
+            let (a_values, b_values) = (values(&a_set), values(&b_set));
+            let covered: BTreeSet<u8> = a.iter().flat_map(|&(l, h)| l..=h).collect();
+            prop_assert_eq!(&a_values, &covered);
+            prop_assert_eq!(a_set.len(), Some(covered.len() as u64));
+            prop_assert!((0..=255).all(|v| a_set.contains(v) == covered.contains(&v)));
+            let union = values(&a_set.union(&b_set));
+            prop_assert_eq!(&union, &(&a_values | &b_values));
+            let mut inserted = a_set.clone();
+            b.iter().for_each(|&(l, h)| inserted.insert(l..=h));
+            prop_assert_eq!(values(&inserted), union);
+            let intersection = values(&a_set.intersection(&b_set));
+            prop_assert_eq!(intersection, &a_values & &b_values);
+            let difference = values(&a_set.difference(&b_set));
+            prop_assert_eq!(difference, &a_values - &b_values);
+            let mut rest = a_set.clone();
+            removed.iter().for_each(|&(l, h)| rest.remove(l..=h));
+            let removed: BTreeSet<u8> = removed.iter().flat_map(|&(l, h)| l..=h).collect();
+            prop_assert_eq!(values(&rest), &a_values - &removed);
+        }
+    }
+}
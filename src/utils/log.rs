@@ -0,0 +1,150 @@
+//! Diagnostics on stderr, kept apart from the results on stdout.
+//!
+//! Solvers report what they are doing through `debug`, `trace` and
+//! `progress`; the runner decides how much of it to show and labels it with
+//! the part being solved.
+
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    io::{self, IsTerminal, Write},
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Errors only.
+    Quiet,
+    /// Status messages and progress bars.
+    #[default]
+    Normal,
+    /// Also what solvers report with `debug`.
+    Verbose,
+    /// Also what solvers report with `trace`.
+    Trace,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+thread_local! {
+    static CONTEXT: RefCell<Option<String>> = const { RefCell::new(None) };
+    /// The last progress shown by this thread, in percent.
+    static SHOWN: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        2 => Verbosity::Verbose,
+        _ => Verbosity::Trace,
+    }
+}
+
+pub fn enabled(verbosity: Verbosity) -> bool {
+    self::verbosity() >= verbosity
+}
+
+/// Runs `f` with messages from this thread labelled by `context`, clearing
+/// any progress bar it leaves behind.
+pub fn with_context<R>(context: String, f: impl FnOnce() -> R) -> R {
+    let previous = CONTEXT.replace(Some(context));
+    let result = f();
+    clear_progress();
+    CONTEXT.set(previous);
+    result
+}
+
+fn write(verbosity: Verbosity, args: fmt::Arguments) {
+    if !enabled(verbosity) {
+        return;
+    }
+    clear_progress();
+    let mut stderr = io::stderr().lock();
+    let _ = match CONTEXT.with_borrow(Clone::clone) {
+        Some(context) => writeln!(stderr, "[{context}] {args}"),
+        None => writeln!(stderr, "{args}"),
+    };
+}
+
+/// A status message for the user, hidden by `-q`.
+pub fn info(args: fmt::Arguments) {
+    write(Verbosity::Normal, args);
+}
+
+/// Detail shown with `-v`.
+pub fn debug(args: fmt::Arguments) {
+    write(Verbosity::Verbose, args);
+}
+
+/// Detail shown with `-vv`.
+pub fn trace(args: fmt::Arguments) {
+    write(Verbosity::Trace, args);
+}
+
+fn bar(done: usize, total: usize) -> String {
+    const WIDTH: usize = 30;
+    let filled = (done * WIDTH)
+        .checked_div(total)
+        .unwrap_or(WIDTH)
+        .min(WIDTH);
+    format!(
+        "[{}{}] {done}/{total}",
+        "#".repeat(filled),
+        "-".repeat(WIDTH - filled)
+    )
+}
+
+/// Reports that `done` of `total` steps are finished, drawn as a progress bar
+/// when stderr is a terminal.
+pub fn progress(done: usize, total: usize) {
+    if !enabled(Verbosity::Normal) || !io::stderr().is_terminal() {
+        return;
+    }
+    let percent = (done * 100).checked_div(total).unwrap_or(100);
+    if SHOWN.get() == Some(percent) {
+        return;
+    }
+    SHOWN.set(Some(percent));
+    let context = CONTEXT.with_borrow(|c| c.clone().map(|c| format!("[{c}] ")));
+    let mut stderr = io::stderr().lock();
+    let _ = write!(
+        stderr,
+        "\r\x1b[K{}{}",
+        context.unwrap_or_default(),
+        bar(done, total)
+    );
+    let _ = stderr.flush();
+}
+
+fn clear_progress() {
+    if SHOWN.take().is_some() {
+        let _ = write!(io::stderr().lock(), "\r\x1b[K");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bar() {
+        assert_eq!(bar(0, 3), format!("[{}] 0/3", "-".repeat(30)));
+        assert_eq!(
+            bar(1, 3),
+            format!("[{}{}] 1/3", "#".repeat(10), "-".repeat(20))
+        );
+        assert_eq!(bar(0, 0), format!("[{}] 0/0", "#".repeat(30)));
+    }
+
+    #[test]
+    fn test_verbosity() {
+        assert!(Verbosity::Trace > Verbosity::Verbose);
+        assert!(enabled(Verbosity::Normal));
+        assert!(!enabled(Verbosity::Verbose));
+    }
+}
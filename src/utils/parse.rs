@@ -0,0 +1,159 @@
+//! Parsers for the pieces that puzzle inputs are made of.
+//!
+//! All of them are built from nom's `complete` parsers, so running out of
+//! input is an error rather than a request for more, and `lines_of` insists
+//! that each item takes up its whole line. Each one leaves whatever follows
+//! it for the next parser, so that they compose; `complete` wraps any of them
+//! into one that rejects leftover input, which is how `parse_all` reads
+//! whole inputs.
+
+use std::str::FromStr;
+
+use nom::{
+    branch::alt,
+    character::complete::{char, digit1, line_ending, multispace0, space0},
+    combinator::{all_consuming, eof, map_res, opt, recognize},
+    error::{Error, ErrorKind},
+    multi::{many0, many1, separated_list1},
+    sequence::{pair, preceded, terminated},
+    IResult,
+};
+
+/// The integer types that numbers can be parsed as.
+pub trait Integer: FromStr {}
+
+/// The integer types that can hold a leading `-`.
+pub trait SignedInteger: Integer {}
+
+macro_rules! integers {
+    ($tr:ident: $($t:ty),*) => {
+        $(impl $tr for $t {})*
+    };
+}
+
+integers!(Integer: u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+integers!(SignedInteger: i8, i16, i32, i64, i128, isize);
+
+/// A number without a sign, of any integer type, failing if it does not fit.
+pub fn unsigned<T: Integer>(s: &str) -> IResult<&str, T> {
+    map_res(digit1, str::parse)(s)
+}
+
+/// A number with an optional leading `-`, of any signed integer type,
+/// failing if it does not fit.
+pub fn signed<T: SignedInteger>(s: &str) -> IResult<&str, T> {
+    map_res(recognize(pair(opt(char('-')), digit1)), str::parse)(s)
+}
+
+/// One or more items separated by commas, as in `1,2,3`.
+pub fn comma_list<'a, O>(
+    item: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(char(','), item)
+}
+
+/// One or more items on a line, separated by spaces and allowing them in
+/// front, as in ` 12  3 45`.
+pub fn spaced<'a, O>(
+    item: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    many1(preceded(space0, item))
+}
+
+/// `N` items separated by commas, as in `1,2,3` for `N = 3`.
+pub fn coords<'a, const N: usize, O>(
+    mut item: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, [O; N]> {
+    move |s| {
+        let (rest, items) = comma_list(&mut item)(s)?;
+        let coords = items
+            .try_into()
+            .map_err(|_| nom::Err::Error(Error::new(s, ErrorKind::Count)))?;
+        Ok((rest, coords))
+    }
+}
+
+/// `item` followed by nothing but whitespace, so that `complete(unsigned)`
+/// rejects `12x` where `unsigned` alone would leave the `x`.
+pub fn complete<'a, O>(
+    item: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O> {
+    all_consuming(terminated(item, multispace0))
+}
+
+/// An item per line, each taking up all of its line, with blank lines and
+/// indentation skipped. Stops at the first line that is not an item.
+pub fn lines_of<'a, O>(
+    mut item: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    move |s| {
+        let end = pair(space0, alt((line_ending, eof)));
+        many0(preceded(multispace0, terminated(&mut item, end)))(s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::parse_all;
+
+    #[test]
+    fn test_integers() {
+        assert_eq!(unsigned::<u8>("255,"), Ok((",", 255)));
+        assert!(unsigned::<u8>("256").is_err());
+        assert!(unsigned::<u32>("-1").is_err());
+        assert_eq!(signed::<i64>("-12 "), Ok((" ", -12)));
+        assert_eq!(signed::<i8>("127"), Ok(("", 127)));
+        assert!(signed::<i8>("-129").is_err());
+        assert!(signed::<i8>("-").is_err());
+    }
+
+    #[test]
+    fn test_lists() {
+        assert_eq!(
+            comma_list(unsigned::<u32>)("1,2,3 "),
+            Ok((" ", vec![1, 2, 3]))
+        );
+        assert!(comma_list(unsigned::<u32>)("").is_err());
+        assert_eq!(
+            spaced(unsigned::<u32>)(" 12  3 45\n6"),
+            Ok(("\n6", vec![12, 3, 45]))
+        );
+    }
+
+    #[test]
+    fn test_coords() {
+        assert_eq!(coords(unsigned::<i64>)("1,2,3"), Ok(("", [1, 2, 3])));
+        assert_eq!(coords(unsigned::<i64>)("1,2"), Ok(("", [1, 2])));
+        assert!(coords::<3, _>(unsigned::<i64>)("1,2").is_err());
+        assert!(coords::<2, _>(unsigned::<i64>)("1,2,3").is_err());
+    }
+
+    #[test]
+    fn test_complete() {
+        assert_eq!(unsigned::<u32>("12x"), Ok(("x", 12)));
+        assert!(complete(unsigned::<u32>)("12x").is_err());
+        assert_eq!(complete(unsigned::<u32>)("12 \n"), Ok(("", 12)));
+        assert!(complete(comma_list(unsigned::<u32>))("1,2,3 4").is_err());
+        assert_eq!(complete(coords(signed::<i32>))("1,-2"), Ok(("", [1, -2])));
+    }
+
+    #[test]
+    fn test_lines_of() {
+        let nums = "
+            1
+            2
+
+            3";
+        assert_eq!(
+            parse_all(nums, lines_of(unsigned::<u32>)),
+            Ok(vec![1, 2, 3])
+        );
+        // A line with more on it than an item ends the list there.
+        let error = parse_all("1\n2 3\n4", lines_of(unsigned::<u32>))
+            .err()
+            .unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(parse_all("", lines_of(unsigned::<u32>)), Ok(vec![]));
+    }
+}
@@ -0,0 +1,206 @@
+use std::{collections::HashMap, hash::Hash};
+
+#[derive(Clone, Copy, Debug)]
+enum Node {
+    Root { size: usize },
+    Child { parent: usize },
+}
+
+/// Disjoint sets of the items `0..len`, merged by size and with paths
+/// compressed on the way, so that a run of merges is close to linear.
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+    nodes: Vec<Node>,
+    components: usize,
+}
+
+impl UnionFind {
+    /// `len` items, each in a component of its own.
+    pub fn new(len: usize) -> Self {
+        Self {
+            nodes: vec![Node::Root { size: 1 }; len],
+            components: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The root of `i`'s component, pointing everything on the way at it.
+    fn find(&mut self, i: usize) -> (usize, usize) {
+        let mut root = i;
+        let size = loop {
+            match self.nodes[root] {
+                Node::Root { size } => break size,
+                Node::Child { parent } => root = parent,
+            }
+        };
+        let mut i = i;
+        while let Node::Child { parent } = self.nodes[i] {
+            self.nodes[i] = Node::Child { parent: root };
+            i = parent;
+        }
+        (root, size)
+    }
+
+    /// Joins the components of `a` and `b`, returning whether they were separate.
+    pub fn merge(&mut self, a: usize, b: usize) -> bool {
+        let (a, a_size) = self.find(a);
+        let (b, b_size) = self.find(b);
+        if a == b {
+            return false;
+        }
+        let (parent, child) = if a_size > b_size { (a, b) } else { (b, a) };
+        self.nodes[child] = Node::Child { parent };
+        self.nodes[parent] = Node::Root {
+            size: a_size + b_size,
+        };
+        self.components -= 1;
+        true
+    }
+
+    /// An item that stands for `i`'s component, the same for all its members
+    /// until the next merge.
+    pub fn component_of(&mut self, i: usize) -> usize {
+        self.find(i).0
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    /// The size of each component.
+    pub fn sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.nodes.iter().filter_map(|n| match n {
+            Node::Root { size } => Some(*size),
+            Node::Child { .. } => None,
+        })
+    }
+
+    /// The members of each component, in order.
+    pub fn components(&mut self) -> impl Iterator<Item = Vec<usize>> {
+        let mut members = vec![Vec::new(); self.len()];
+        for i in 0..self.len() {
+            members[self.find(i).0].push(i);
+        }
+        members.into_iter().filter(|m| !m.is_empty())
+    }
+}
+
+/// A `UnionFind` over any keys, numbered in the order they are given.
+#[derive(Clone, Debug)]
+pub struct KeyedUnionFind<K> {
+    keys: Vec<K>,
+    indices: HashMap<K, usize>,
+    sets: UnionFind,
+}
+
+impl<K: Hash + Eq + Clone> KeyedUnionFind<K> {
+    /// Each key in a component of its own; repeated keys are only counted once.
+    pub fn new(keys: impl IntoIterator<Item = K>) -> Self {
+        let mut indices = HashMap::new();
+        let mut unique = Vec::new();
+        for key in keys {
+            indices.entry(key.clone()).or_insert_with(|| {
+                unique.push(key);
+                unique.len() - 1
+            });
+        }
+        Self {
+            sets: UnionFind::new(unique.len()),
+            keys: unique,
+            indices,
+        }
+    }
+
+    /// Joins the components of `a` and `b`, returning whether they were
+    /// separate, or `None` if either is unknown.
+    pub fn merge(&mut self, a: &K, b: &K) -> Option<bool> {
+        let (&a, &b) = (self.indices.get(a)?, self.indices.get(b)?);
+        Some(self.sets.merge(a, b))
+    }
+
+    /// A number that stands for `key`'s component, or `None` if it is unknown.
+    pub fn component_of(&mut self, key: &K) -> Option<usize> {
+        let &i = self.indices.get(key)?;
+        Some(self.sets.component_of(i))
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.sets.component_count()
+    }
+
+    pub fn sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.sets.sizes()
+    }
+
+    /// The members of each component, in the order the keys were given.
+    pub fn components(&mut self) -> impl Iterator<Item = Vec<&K>> {
+        let keys = &self.keys;
+        self.sets
+            .components()
+            .map(move |members| members.into_iter().map(|i| &keys[i]).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let mut sets = UnionFind::new(5);
+        assert_eq!(sets.component_count(), 5);
+        assert!(sets.merge(0, 1));
+        assert!(sets.merge(3, 4));
+        assert!(sets.merge(1, 4));
+        assert!(!sets.merge(0, 3));
+        assert_eq!(sets.component_count(), 2);
+        assert_eq!(sets.component_of(0), sets.component_of(4));
+        assert_ne!(sets.component_of(0), sets.component_of(2));
+        let mut sizes: Vec<_> = sets.sizes().collect();
+        sizes.sort();
+        assert_eq!(sizes, [1, 4]);
+        let mut components: Vec<_> = sets.components().collect();
+        components.sort();
+        assert_eq!(components, [vec![0, 1, 3, 4], vec![2]]);
+    }
+
+    #[test]
+    fn test_path_compression() {
+        // Merging roots of equal-sized trees builds the path 0, 1, 3, 7, 15.
+        let mut sets = UnionFind::new(16);
+        for step in [1, 2, 4, 8] {
+            for i in (0..16).step_by(2 * step) {
+                sets.merge(i + step - 1, i + 2 * step - 1);
+            }
+        }
+        let parent = |sets: &UnionFind, i| match sets.nodes[i] {
+            Node::Root { .. } => None,
+            Node::Child { parent } => Some(parent),
+        };
+        let path = [0, 1, 3, 7];
+        assert!(path.windows(2).all(|w| parent(&sets, w[0]) == Some(w[1])));
+        assert_eq!(sets.component_of(0), 15);
+        assert!(path.iter().all(|&i| parent(&sets, i) == Some(15)));
+    }
+
+    #[test]
+    fn test_keyed() {
+        let mut sets = KeyedUnionFind::new(["a", "b", "c", "a"]);
+        assert_eq!(sets.component_count(), 3);
+        assert_eq!(sets.merge(&"a", &"c"), Some(true));
+        assert_eq!(sets.merge(&"c", &"a"), Some(false));
+        assert_eq!(sets.merge(&"a", &"d"), None);
+        assert_eq!(sets.component_of(&"a"), sets.component_of(&"c"));
+        assert_eq!(sets.component_of(&"d"), None);
+        let mut components: Vec<_> = sets.components().collect();
+        components.sort();
+        assert_eq!(components, [vec![&"a", &"c"], vec![&"b"]]);
+    }
+}
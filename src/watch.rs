@@ -0,0 +1,86 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+/// How often the watched files are checked.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Polls a set of files for changes to their modification times.
+pub struct Watcher {
+    paths: Vec<PathBuf>,
+    seen: Vec<Option<SystemTime>>,
+    interval: Duration,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl Watcher {
+    pub fn new(paths: Vec<PathBuf>, interval: Duration) -> Self {
+        let seen = paths.iter().map(|p| modified(p)).collect();
+        Self {
+            paths,
+            seen,
+            interval,
+        }
+    }
+
+    /// The files that changed since the last call, or since the watcher was
+    /// created. A file that appears or disappears counts as changed.
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for (path, seen) in self.paths.iter().zip(&mut self.seen) {
+            let now = modified(path);
+            if now != *seen {
+                *seen = now;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+
+    /// Blocks until at least one file changes, returning the changed files.
+    pub fn wait(&mut self) -> Vec<PathBuf> {
+        loop {
+            let changed = self.changed();
+            if !changed.is_empty() {
+                return changed;
+            }
+            thread::sleep(self.interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn test_changed() {
+        let dir = env::temp_dir().join(format!("aoc-watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (a, b) = (dir.join("a.txt"), dir.join("b.txt"));
+        fs::write(&a, "1").unwrap();
+        let _ = fs::remove_file(&b);
+        let mut watcher = Watcher::new(vec![a.clone(), b.clone()], Duration::from_millis(10));
+        assert!(watcher.changed().is_empty());
+        fs::write(&b, "2").unwrap();
+        assert_eq!(watcher.wait(), std::slice::from_ref(&b));
+        let later = SystemTime::now() + Duration::from_secs(1);
+        fs::File::options()
+            .write(true)
+            .open(&a)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(watcher.wait(), [a]);
+        assert!(watcher.changed().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,34 @@
+use adventofcode::{
+    day1::Day1,
+    solution::{self, Solution, DAYS},
+    utils::{IntervalSet, KeyedUnionFind},
+};
+
+#[test]
+fn test_solution_api() {
+    let turns = Day1::parse("L68\nL30\nR48").unwrap();
+    assert_eq!(Day1::part1(&turns), 1);
+    assert_eq!(Day1::part2(&turns), Some(2));
+}
+
+#[test]
+fn test_registry() {
+    assert!(DAYS.iter().any(|d| d.day == 10));
+    let day = solution::find(1).unwrap();
+    assert_eq!(day.run("L68\nL30\nR48", 2), Ok(Some(2.into())));
+    assert!(day.run("L68\nX30", 1).is_err());
+}
+
+#[test]
+fn test_helpers() {
+    let fresh: IntervalSet<u64> = [5..=9, 1..=5].into_iter().collect();
+    assert_eq!(fresh.iter().collect::<Vec<_>>(), [1..=9]);
+    assert_eq!(fresh.len(), Some(9));
+    let mut uf = KeyedUnionFind::new([(0, 0, 0), (1, 1, 1), (2, 2, 2)]);
+    assert_eq!(uf.merge(&(0, 0, 0), &(2, 2, 2)), Some(true));
+    assert_eq!(uf.merge(&(2, 2, 2), &(0, 0, 0)), Some(false));
+    assert_eq!(uf.component_count(), 2);
+    let mut sizes: Vec<_> = uf.sizes().collect();
+    sizes.sort();
+    assert_eq!(sizes, [1, 2]);
+}